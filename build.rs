@@ -0,0 +1,20 @@
+use std::process::Command;
+
+// Incrusta el commit de git en el binario (`GIT_COMMIT`) para los manifiestos de experimento.
+fn main() {
+    let git = |args: &[&str]| {
+        Command::new("git").args(args).output().ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let commit = match git(&["rev-parse", "HEAD"]) {
+        // Un árbol con cambios sin confirmar no se corresponde exactamente con el commit
+        Some(hash) if git(&["status", "--porcelain", "--untracked-files=no"]).is_some_and(|status| !status.is_empty()) => format!("{}-dirty", hash),
+        Some(hash) => hash,
+        None => "desconocido".to_string(),
+    };
+    println!("cargo:rustc-env=GIT_COMMIT={}", commit);
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/index");
+    println!("cargo:rerun-if-changed=src");
+}
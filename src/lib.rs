@@ -0,0 +1,1719 @@
+//! Motor de física de 'El Armónico 137': constantes, genoma de un universo (`CosmicLaw`), el
+//! `PhysicsEngine` con sus puntuaciones por nivel y la escalera de fitness. El binario
+//! `rust-universe` es solo la interfaz de línea de comandos sobre esta biblioteca.
+
+use std::error::Error;
+use rand::prelude::*;
+use rand::Rng;
+use rand_distr::StandardNormal;
+use std::f64::consts::PI;
+use std::fs;
+use std::ops::Deref;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+use serde::{Deserialize, Serialize};
+
+// --- LEVEL 0: CONSTANTES FÍSICAS INMUTABLES (CODATA 2018) ---
+pub const C: f64 = 299_792_458.0;
+pub const H_BAR: f64 = 1.054_571_817e-34;
+pub const EPSILON_0: f64 = 8.854_187_812_8e-12;
+pub const K_B: f64 = 1.380_649e-23; // Constante de Boltzmann
+pub const M_SOLAR: f64 = 1.989e30; // Referencia astronómica, no forma parte de CODATA
+
+/// Conjunto de constantes "meta-ley" contra el que se evalúan los genomas.
+/// Por defecto son los valores CODATA 2018; se puede inyectar otro conjunto en `PhysicsEngine::new`.
+#[derive(Debug, Clone, Copy)]
+pub struct PhysicalConstants {
+    pub c: f64,
+    pub h_bar: f64,
+    pub epsilon_0: f64,
+    pub k_b: f64,
+    pub m_solar: f64,
+}
+
+impl PhysicalConstants {
+    pub fn codata_2018() -> Self {
+        Self { c: C, h_bar: H_BAR, epsilon_0: EPSILON_0, k_b: K_B, m_solar: M_SOLAR }
+    }
+
+    /// Constante de estructura fina para una carga elemental `e` dada.
+    pub fn fine_structure(&self, e: f64) -> f64 {
+        e.powi(2) / (4.0 * PI * self.epsilon_0 * self.h_bar * self.c)
+    }
+}
+
+impl Default for PhysicalConstants {
+    fn default() -> Self {
+        Self::codata_2018()
+    }
+}
+
+// --- LEVEL 1: PLANTILLAS DE PARTÍCULAS ---
+#[derive(Debug, Clone)]
+pub struct ParticleBlueprint {
+    pub name: &'static str,
+    pub spin: f64,
+}
+
+lazy_static::lazy_static! {
+    // 1st Gen
+    pub static ref UP_QUARK: ParticleBlueprint = ParticleBlueprint { name: "Up", spin: 0.5 };
+    pub static ref DOWN_QUARK: ParticleBlueprint = ParticleBlueprint { name: "Down", spin: 0.5 };
+    pub static ref ELECTRON: ParticleBlueprint = ParticleBlueprint { name: "Electron", spin: 0.5 };
+    // 2nd Gen
+    pub static ref CHARM_QUARK: ParticleBlueprint = ParticleBlueprint { name: "Charm", spin: 0.5 };
+    pub static ref STRANGE_QUARK: ParticleBlueprint = ParticleBlueprint { name: "Strange", spin: 0.5 };
+    pub static ref MUON: ParticleBlueprint = ParticleBlueprint { name: "Muon", spin: 0.5 };
+    // 3rd Gen
+    pub static ref TOP_QUARK: ParticleBlueprint = ParticleBlueprint { name: "Top", spin: 0.5 };
+    pub static ref BOTTOM_QUARK: ParticleBlueprint = ParticleBlueprint { name: "Bottom", spin: 0.5 };
+    pub static ref TAUON: ParticleBlueprint = ParticleBlueprint { name: "Tauon", spin: 0.5 };
+}
+
+// --- LEVEL 2: EL GENOMA DE UN UNIVERSO ---
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[allow(non_snake_case)]
+pub struct CosmicLaw {
+    pub G: f64,
+    pub e: f64,
+    pub alpha_s: f64,
+    pub alpha_w: f64,
+    pub mass_up_quark: f64, pub mass_down_quark: f64, pub mass_electron: f64,
+    pub mass_charm_quark: f64, pub mass_strange_quark: f64, pub mass_muon: f64,
+    pub mass_top_quark: f64, pub mass_bottom_quark: f64, pub mass_tauon: f64,
+    /// Parámetro de densidad de la materia oscura, `Ω_dm`. Los genomas anteriores a este gen
+    /// reciben el valor de nuestro universo.
+    #[serde(default = "real_omega_dm")]
+    pub omega_dm: f64,
+}
+
+/// `Ω_dm` de nuestro universo (Planck 2018).
+pub const REAL_OMEGA_DM: f64 = 0.265;
+
+fn real_omega_dm() -> f64 {
+    REAL_OMEGA_DM
+}
+
+/// Genoma de nuestro universo (CODATA 2018 y masas de quarks "corrientes" del PDG).
+/// `alpha_s` es el valor a la escala del Z y `alpha_w` el acoplamiento de Fermi adimensional `G_F·m_p²`.
+pub const REAL_UNIVERSE: CosmicLaw = CosmicLaw {
+    G: 6.674_30e-11,
+    e: 1.602_176_634e-19,
+    alpha_s: 0.118,
+    alpha_w: 1.027e-5,
+    mass_up_quark: 3.851e-30,       // 2.16 MeV
+    mass_down_quark: 8.325e-30,     // 4.67 MeV
+    mass_electron: 9.109_383_7e-31,
+    mass_charm_quark: 2.264e-27,    // 1.27 GeV
+    mass_strange_quark: 1.665e-28,  // 93.4 MeV
+    mass_muon: 1.883_531_6e-28,
+    mass_top_quark: 3.078_5e-25,    // 172.69 GeV
+    mass_bottom_quark: 7.451_5e-27, // 4.18 GeV
+    mass_tauon: 3.167_54e-27,
+    omega_dm: REAL_OMEGA_DM,
+};
+
+/// Descripción de un gen: nombre del campo y rango de muestreo aleatorio.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct GeneSpec {
+    pub name: &'static str,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Identificador de cada gen del genoma, en el mismo orden que `GENES` y `CosmicLaw::genes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gene {
+    G,
+    E,
+    AlphaS,
+    AlphaW,
+    MassUpQuark,
+    MassDownQuark,
+    MassElectron,
+    MassCharmQuark,
+    MassStrangeQuark,
+    MassMuon,
+    MassTopQuark,
+    MassBottomQuark,
+    MassTauon,
+    OmegaDm,
+}
+
+/// Número de genes del genoma.
+pub const GENE_COUNT: usize = 14;
+
+impl Gene {
+    pub const ALL: [Gene; GENE_COUNT] = [
+        Gene::G, Gene::E, Gene::AlphaS, Gene::AlphaW,
+        Gene::MassUpQuark, Gene::MassDownQuark, Gene::MassElectron,
+        Gene::MassCharmQuark, Gene::MassStrangeQuark, Gene::MassMuon,
+        Gene::MassTopQuark, Gene::MassBottomQuark, Gene::MassTauon,
+        Gene::OmegaDm,
+    ];
+
+    pub fn spec(self) -> &'static GeneSpec {
+        &GENES[self as usize]
+    }
+
+    pub fn category(self) -> GeneCategory {
+        match self {
+            Gene::G | Gene::E | Gene::AlphaS | Gene::AlphaW => GeneCategory::Coupling,
+            Gene::MassUpQuark | Gene::MassCharmQuark | Gene::MassTopQuark => GeneCategory::UpTypeQuark,
+            Gene::MassDownQuark | Gene::MassStrangeQuark | Gene::MassBottomQuark => GeneCategory::DownTypeQuark,
+            Gene::MassElectron | Gene::MassMuon | Gene::MassTauon => GeneCategory::ChargedLepton,
+            Gene::OmegaDm => GeneCategory::Cosmological,
+        }
+    }
+}
+
+/// Familia física de un gen. Las tres familias de masas contienen una partícula por generación.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeneCategory {
+    Coupling,
+    UpTypeQuark,
+    DownTypeQuark,
+    ChargedLepton,
+    Cosmological,
+}
+
+impl GeneCategory {
+    /// Familias cuyos genes puede permutar la mutación de intercambio (`--swap-rate`).
+    pub const SWAPPABLE: [GeneCategory; 3] = [
+        GeneCategory::UpTypeQuark, GeneCategory::DownTypeQuark, GeneCategory::ChargedLepton,
+    ];
+
+    pub fn genes(self) -> impl Iterator<Item = Gene> {
+        Gene::ALL.into_iter().filter(move |gene| gene.category() == self)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GeneCategory::Coupling => "couplings",
+            GeneCategory::UpTypeQuark => "up_quarks",
+            GeneCategory::DownTypeQuark => "down_quarks",
+            GeneCategory::ChargedLepton => "charged_leptons",
+            GeneCategory::Cosmological => "cosmological",
+        }
+    }
+}
+
+/// Orden en que `CosmicLaw::random` sortea los genes. No coincide con `GENES` (los quarks pesados
+/// y el muón van en otro orden), pero cambiarlo alteraría los universos de cualquier semilla ya
+/// publicada (`replay`, `reproduce`).
+const RANDOM_DRAW_ORDER: [Gene; GENE_COUNT] = [
+    Gene::G, Gene::E, Gene::AlphaS, Gene::AlphaW,
+    Gene::MassUpQuark, Gene::MassDownQuark, Gene::MassElectron,
+    Gene::MassStrangeQuark, Gene::MassCharmQuark, Gene::MassMuon,
+    Gene::MassBottomQuark, Gene::MassTopQuark, Gene::MassTauon,
+    Gene::OmegaDm,
+];
+
+/// Tabla de genes en el mismo orden que `CosmicLaw::genes`.
+pub const GENES: [GeneSpec; GENE_COUNT] = [
+    GeneSpec { name: "G", min: 6.674e-11, max: 6.674e-10 },
+    GeneSpec { name: "e", min: 0.5e-19, max: 2.5e-19 },
+    GeneSpec { name: "alpha_s", min: 0.1, max: 2.0 },
+    GeneSpec { name: "alpha_w", min: 1.0e-9, max: 1.0e-4 },
+    GeneSpec { name: "mass_up_quark", min: 1.0e-30, max: 6.0e-30 },
+    GeneSpec { name: "mass_down_quark", min: 1.0e-30, max: 1.3e-29 },
+    GeneSpec { name: "mass_electron", min: 1.0e-31, max: 1.0e-30 },
+    GeneSpec { name: "mass_charm_quark", min: 1.0e-29, max: 1.0e-27 },
+    GeneSpec { name: "mass_strange_quark", min: 1.0e-29, max: 1.0e-28 },
+    GeneSpec { name: "mass_muon", min: 1.0e-29, max: 1.0e-27 },
+    GeneSpec { name: "mass_top_quark", min: 1.0e-28, max: 1.0e-25 },
+    GeneSpec { name: "mass_bottom_quark", min: 1.0e-28, max: 1.0e-27 },
+    GeneSpec { name: "mass_tauon", min: 1.0e-28, max: 1.0e-26 },
+    GeneSpec { name: "omega_dm", min: 0.0265, max: 2.65 },
+];
+
+/// Rango `{min, max}` de un gen en un archivo `--ranges`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct GeneRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Rangos de muestreo e hipermutación de cada gen, en el orden de `GENES`. Se cargan con
+/// `--ranges <archivo.json>` (`{"alpha_s": {"min": 0.05, "max": 0.5}, ...}`); los genes ausentes
+/// conservan su rango de `GENES`.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RangeConfig(pub [GeneSpec; GENE_COUNT]);
+
+/// Rangos activos en el proceso, fijados una vez al arrancar desde `--ranges`.
+pub static RANGES: OnceLock<RangeConfig> = OnceLock::new();
+
+impl RangeConfig {
+    pub const DEFAULT: RangeConfig = RangeConfig(GENES);
+
+    /// Cada rango debe cumplir `0 < min < max`: el genoma se muta en escala logarítmica, así que
+    /// ningún gen puede muestrearse en cero ni en valores negativos.
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let named: HashMap<String, GeneRange> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let mut ranges = Self::DEFAULT;
+        for (name, range) in named {
+            let index = GENES.iter().position(|spec| spec.name == name)
+                .ok_or_else(|| format!("Gen desconocido en {}: '{}'", path, name))?;
+            if !(range.min.is_finite() && range.max.is_finite() && range.min > 0.0 && range.min < range.max) {
+                return Err(format!("El rango de '{}' necesita 0 < min < max finitos (es [{}, {}])", name, range.min, range.max).into());
+            }
+            ranges.0[index].min = range.min;
+            ranges.0[index].max = range.max;
+        }
+        Ok(ranges)
+    }
+
+    /// Los rangos fijados al arrancar, o los de `GENES`.
+    pub fn active() -> &'static RangeConfig {
+        RANGES.get().unwrap_or(&Self::DEFAULT)
+    }
+
+    pub fn spec(&self, gene: Gene) -> &GeneSpec {
+        &self.0[gene as usize]
+    }
+}
+
+// --- PARÁMETROS DEL MODELO NUCLEAR ---
+/// Julios por megaelectronvoltio.
+pub const MEV: f64 = 1.602_176_634e-13;
+/// Energía de enlace del deuterón en nuestro universo (2.224 MeV).
+pub const DEUTERON_BINDING_REAL: f64 = 2.224_566 * MEV;
+/// Masas del protón y del neutrón en nuestro universo (CODATA 2018).
+pub const PROTON_MASS_REAL: f64 = 1.672_621_923_69e-27;
+pub const NEUTRON_MASS_REAL: f64 = 1.674_927_498_04e-27;
+/// Masa de un nucleón que no procede de sus quarks de valencia sino de la interacción fuerte
+/// (~99%): la del neutrón real menos su contenido `udd`. No depende del genoma.
+pub const NUCLEON_QCD_MASS: f64 =
+    NEUTRON_MASS_REAL - (REAL_UNIVERSE.mass_up_quark + 2.0 * REAL_UNIVERSE.mass_down_quark);
+/// Autoenergía electromagnética del protón real (~1.2 MeV): lo que falta a su masa tras la parte
+/// fuerte y el contenido `uud`. Escala como `e²`; el neutrón, neutro, no la tiene.
+pub const PROTON_EM_MASS_REAL: f64 =
+    PROTON_MASS_REAL - NUCLEON_QCD_MASS - (2.0 * REAL_UNIVERSE.mass_up_quark + REAL_UNIVERSE.mass_down_quark);
+/// Margen de nuestro hidrógeno frente a la captura electrónica, `(m_n − m_p − m_e)` (~0.78 MeV).
+pub const HYDROGEN_MARGIN_REAL: f64 = NEUTRON_MASS_REAL - PROTON_MASS_REAL - REAL_UNIVERSE.mass_electron;
+/// Factor que ajusta el modelo hidrogenoide `½·alpha_s²·μ·c²` a la energía de enlace real del deuterón
+/// (~0.68): el potencial nuclear no es coulombiano, pero el enlace conserva su escala en `alpha_s² · μ`.
+pub const DEUTERON_BINDING_CALIBRATION: f64 = DEUTERON_BINDING_REAL
+    / (0.5 * REAL_UNIVERSE.alpha_s * REAL_UNIVERSE.alpha_s
+        * (PROTON_MASS_REAL * NEUTRON_MASS_REAL / (PROTON_MASS_REAL + NEUTRON_MASS_REAL)) * C * C);
+
+// --- PARÁMETROS DEL MODELO ESTELAR ---
+/// Temperatura del núcleo solar (K), a la que se evalúa por defecto el ritmo de fusión pp.
+pub const SOLAR_CORE_TEMPERATURE: f64 = 1.5e7;
+/// Exponente de `G` y de `m_p` en la relación masa-luminosidad de Eddington (`L ∝ G⁴·m_p⁴·M³`).
+pub const EDDINGTON_LUMINOSITY_EXPONENT: f64 = 4.0;
+
+// --- PARÁMETROS COSMOLÓGICOS ---
+/// Parámetro de densidad bariónica, fijo: el genoma solo varía la materia oscura.
+pub const OMEGA_BARYON: f64 = 0.049;
+
+// --- PARÁMETROS DE ELEMENTOS PESADOS Y AGUJEROS NEGROS ---
+/// Acoplamiento fuerte óptimo para la síntesis de elementos pesados (valor medido en M_Z).
+pub const HEAVY_ELEMENTS_ALPHA_S_OPTIMAL: f64 = 0.118;
+/// Masa estelar mínima (en masas solares) de un progenitor de agujero negro, objetivo por defecto
+/// de la masa de Chandrasekhar.
+pub const BLACK_HOLE_PROGENITOR_MASS: f64 = 8.0;
+
+// --- PARÁMETROS DE ROBUSTEZ ---
+/// Desviación típica (en logaritmo natural) de la perturbación de cada gen: ~1%.
+pub const ROBUSTNESS_PERTURBATION: f64 = 0.01;
+
+// --- TOLERANCIAS DE LAS PUNTUACIONES ---
+/// Anchuras de las mesetas de cada sub-puntuación: cuánto puede alejarse un universo del valor
+/// ideal antes de perder la puntuación. Se cargan con `--tolerances <archivo.json>`; los campos
+/// ausentes toman su valor por defecto.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToleranceConfig {
+    /// Anchura (en unidades de logaritmo natural) de los bordes de la ventana del deuterio.
+    pub nuclear_window_softness: f64,
+    /// Enlace máximo, relativo al real, antes de que el diprotón quede ligado. Un acoplamiento fuerte
+    /// ~10% mayor bastaría para ligarlo; como `B ∝ alpha_s²`, eso equivale a un enlace ~21% mayor.
+    pub diproton_binding_limit: f64,
+    /// Anchura logarítmica con la que se penaliza una inversión en la jerarquía de leptones.
+    pub lepton_ordering_softness: f64,
+    /// Anchura logarítmica con la que se penaliza una inversión en la jerarquía de quarks.
+    pub quark_ordering_softness: f64,
+    /// Desajuste tolerado (en e-foldings) entre el ritmo de fusión disponible y el requerido. Es una
+    /// ventana ancha porque la estrella absorbe parte del desajuste ajustando su temperatura central.
+    pub stellar_log_rate_tolerance: f64,
+    /// Error relativo de `alpha_s` respecto al óptimo a partir del cual no hay elementos pesados; la
+    /// puntuación cae linealmente de 1.0 a 0.5 a lo largo de la meseta y vale 0 fuera de ella.
+    pub heavy_elements_alpha_s_cutoff: f64,
+    /// Desviación típica (en décadas) de la masa de Chandrasekhar respecto al progenitor de agujero negro.
+    pub black_hole_log_mass_sigma: f64,
+    /// Desviación típica (en logaritmo natural) del cociente materia oscura / bariónica respecto al real.
+    pub dark_matter_log_ratio_sigma: f64,
+}
+
+impl Default for ToleranceConfig {
+    fn default() -> Self {
+        Self {
+            nuclear_window_softness: 0.1,
+            diproton_binding_limit: 1.21,
+            lepton_ordering_softness: 0.1,
+            quark_ordering_softness: 0.1,
+            stellar_log_rate_tolerance: 10.0,
+            heavy_elements_alpha_s_cutoff: 0.5,
+            black_hole_log_mass_sigma: 1.0,
+            dark_matter_log_ratio_sigma: 1.0,
+        }
+    }
+}
+
+/// Tolerancias activas en el proceso, fijadas una vez al arrancar desde `--tolerances`.
+pub static TOLERANCES: OnceLock<ToleranceConfig> = OnceLock::new();
+
+impl ToleranceConfig {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let tolerances: ToleranceConfig = serde_json::from_str(&fs::read_to_string(path)?)?;
+        tolerances.validate()?;
+        Ok(tolerances)
+    }
+
+    /// Las tolerancias fijadas al arrancar, o las de por defecto.
+    pub fn active() -> ToleranceConfig {
+        TOLERANCES.get().copied().unwrap_or_default()
+    }
+
+    /// Tolerancias con todas las anchuras multiplicadas por `factor` (> 1 relaja, < 1 endurece). El
+    /// límite del diprotón es un cociente, así que se escala su exceso logarítmico sobre 1.
+    pub fn scaled(&self, factor: f64) -> Self {
+        Self {
+            nuclear_window_softness: self.nuclear_window_softness * factor,
+            diproton_binding_limit: self.diproton_binding_limit.powf(factor),
+            lepton_ordering_softness: self.lepton_ordering_softness * factor,
+            quark_ordering_softness: self.quark_ordering_softness * factor,
+            stellar_log_rate_tolerance: self.stellar_log_rate_tolerance * factor,
+            heavy_elements_alpha_s_cutoff: self.heavy_elements_alpha_s_cutoff * factor,
+            black_hole_log_mass_sigma: self.black_hole_log_mass_sigma * factor,
+            dark_matter_log_ratio_sigma: self.dark_matter_log_ratio_sigma * factor,
+        }
+    }
+
+    /// Todas las anchuras deben ser finitas y positivas; el límite del diprotón, además, mayor que 1.
+    pub fn validate(&self) -> Result<(), String> {
+        let named = [
+            ("nuclear_window_softness", self.nuclear_window_softness),
+            ("diproton_binding_limit", self.diproton_binding_limit),
+            ("lepton_ordering_softness", self.lepton_ordering_softness),
+            ("quark_ordering_softness", self.quark_ordering_softness),
+            ("stellar_log_rate_tolerance", self.stellar_log_rate_tolerance),
+            ("heavy_elements_alpha_s_cutoff", self.heavy_elements_alpha_s_cutoff),
+            ("black_hole_log_mass_sigma", self.black_hole_log_mass_sigma),
+            ("dark_matter_log_ratio_sigma", self.dark_matter_log_ratio_sigma),
+        ];
+        for (name, value) in named {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(format!("La tolerancia '{}' debe ser finita y positiva (valor: {})", name, value));
+            }
+        }
+        if self.diproton_binding_limit <= 1.0 {
+            return Err(format!("'diproton_binding_limit' debe ser mayor que 1 (valor: {})", self.diproton_binding_limit));
+        }
+        Ok(())
+    }
+}
+
+// --- PARÁMETROS DE REFERENCIA ESTELARES ---
+/// Valores de referencia del modelo estelar, que por defecto suponen estrellas de tipo solar. Se
+/// cargan con `--stellar-config <archivo.json>`; los campos ausentes toman su valor por defecto.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StellarParams {
+    /// Temperatura del núcleo (K) a la que se evalúa el ritmo de fusión pp.
+    pub core_temperature: f64,
+    /// Masa (en masas solares) del progenitor de agujero negro al que debe acercarse la masa de Chandrasekhar.
+    pub black_hole_progenitor_mass: f64,
+}
+
+impl Default for StellarParams {
+    fn default() -> Self {
+        Self { core_temperature: SOLAR_CORE_TEMPERATURE, black_hole_progenitor_mass: BLACK_HOLE_PROGENITOR_MASS }
+    }
+}
+
+/// Parámetros estelares activos en el proceso, fijados una vez al arrancar desde `--stellar-config`.
+pub static STELLAR_PARAMS: OnceLock<StellarParams> = OnceLock::new();
+
+impl StellarParams {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let params: StellarParams = serde_json::from_str(&fs::read_to_string(path)?)?;
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Los parámetros fijados al arrancar, o los solares por defecto.
+    pub fn active() -> StellarParams {
+        STELLAR_PARAMS.get().copied().unwrap_or_default()
+    }
+
+    /// Ambos valores deben ser finitos y positivos: una temperatura nula o negativa dejaría sin
+    /// sentido el exponente de Gamow, y una masa nula, el logaritmo del objetivo.
+    pub fn validate(&self) -> Result<(), String> {
+        let named = [
+            ("core_temperature", self.core_temperature),
+            ("black_hole_progenitor_mass", self.black_hole_progenitor_mass),
+        ];
+        for (name, value) in named {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(format!("El parámetro estelar '{}' debe ser finito y positivo (valor: {})", name, value));
+            }
+        }
+        Ok(())
+    }
+}
+
+// --- PUNTUACIONES TIPADAS ---
+/// Fitness total de un universo, acotado en [0, 1].
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize)]
+pub struct Fitness(pub f64);
+
+/// Sub-puntuación de un criterio físico individual, acotada en [0, 1].
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize)]
+pub struct Score(pub f64);
+
+/// Acota un valor a [0, 1]; un NaN es un error lógico (se detecta en debug y vale 0.0 en release).
+fn unit_interval(value: f64) -> f64 {
+    debug_assert!(!value.is_nan(), "puntuación NaN");
+    if value.is_nan() { 0.0 } else { value.clamp(0.0, 1.0) }
+}
+
+impl Fitness {
+    pub fn new(value: f64) -> Self {
+        Self(unit_interval(value))
+    }
+}
+
+impl Score {
+    pub const ZERO: Score = Score(0.0);
+
+    pub fn new(value: f64) -> Self {
+        Self(unit_interval(value))
+    }
+}
+
+impl Deref for Fitness {
+    type Target = f64;
+    fn deref(&self) -> &f64 {
+        &self.0
+    }
+}
+
+impl Deref for Score {
+    type Target = f64;
+    fn deref(&self) -> &f64 {
+        &self.0
+    }
+}
+
+impl From<Fitness> for f64 {
+    fn from(fitness: Fitness) -> f64 {
+        fitness.0
+    }
+}
+
+impl From<Score> for f64 {
+    fn from(score: Score) -> f64 {
+        score.0
+    }
+}
+
+fn logistic(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+// --- MOTOR DE FÍSICA ---
+/// `τ` de la reacción p + p para un protón de masa `m_proton` y acoplamiento `alpha`
+/// (ver `PhysicsEngine::pp_gamow_exponent`).
+fn gamow_exponent(k: &PhysicalConstants, m_proton: f64, alpha: f64, t_core: f64) -> Option<f64> {
+    let thermal_energy = k.k_b * t_core;
+    if m_proton <= 0.0 || !thermal_energy.is_finite() || thermal_energy <= 0.0 { return None; }
+    let m_reduced = m_proton / 2.0;
+    let gamow_energy = 2.0 * m_reduced * k.c.powi(2) * (PI * alpha).powi(2);
+    Some(3.0 * (gamow_energy / (4.0 * thermal_energy)).cbrt())
+}
+
+/// Exponente de Gamow de nuestro universo (`REAL_UNIVERSE`) con las constantes `k`.
+fn reference_gamow_exponent(k: &PhysicalConstants, t_core: f64) -> Option<f64> {
+    gamow_exponent(k, REAL_UNIVERSE.proton_mass(), k.fine_structure(REAL_UNIVERSE.e), t_core)
+}
+
+#[derive(Debug)]
+pub struct PhysicsEngine {
+    pub laws: CosmicLaw,
+    pub constants: PhysicalConstants,
+    pub tolerances: ToleranceConfig,
+    pub stellar: StellarParams,
+    pub alpha: f64,
+    /// Exponente de Gamow de nuestro universo con estas constantes y `stellar.core_temperature`,
+    /// la referencia de `calculate_stellar_viability`. Se calcula una sola vez al construir el motor.
+    reference_gamow_exponent: Option<f64>,
+}
+
+// --- IMPLEMENTACIÓN DEL MOTOR DE FÍSICA (v18.0 - CON GRADIENTES SUAVES) ---
+// --- IMPLEMENTACIÓN DEL MOTOR DE FÍSICA (v18.1 - COMPLETO) ---
+impl PhysicsEngine {
+    pub fn new(laws: CosmicLaw, constants: PhysicalConstants) -> Self {
+        let alpha = constants.fine_structure(laws.e);
+        let stellar = StellarParams::active();
+        let reference_gamow_exponent = reference_gamow_exponent(&constants, stellar.core_temperature);
+        Self { laws, constants, tolerances: ToleranceConfig::active(), stellar, alpha, reference_gamow_exponent }
+    }
+
+    /// Copia del motor que puntúa con otras tolerancias en lugar de las activas.
+    pub fn with_tolerances(mut self, tolerances: ToleranceConfig) -> Self {
+        self.tolerances = tolerances;
+        self
+    }
+
+    /// Copia del motor que puntúa con otros parámetros estelares en lugar de los activos.
+    pub fn with_stellar_params(mut self, stellar: StellarParams) -> Self {
+        self.reference_gamow_exponent = reference_gamow_exponent(&self.constants, stellar.core_temperature);
+        self.stellar = stellar;
+        self
+    }
+
+    /// Motor inicializado con las constantes de nuestro universo (`REAL_UNIVERSE`).
+    pub fn from_real_universe() -> Self {
+        Self::new(REAL_UNIVERSE, PhysicalConstants::default())
+    }
+
+    /// Copia del motor con un gen sustituido, p. ej.
+    /// `PhysicsEngine::from_real_universe().with_override(Gene::AlphaS, 0.2)`.
+    pub fn with_override(&self, gene: Gene, value: f64) -> Self {
+        Self::new(self.laws.with_gene(gene, value), self.constants)
+    }
+
+    pub fn bohr_radius(&self) -> f64 {
+        let k = &self.constants;
+        4.0 * PI * k.epsilon_0 * k.h_bar.powi(2) / (self.laws.mass_electron * self.laws.e.powi(2))
+    }
+
+    pub fn chandrasekhar_mass(&self) -> f64 {
+        let k = &self.constants;
+        let m_proton = self.laws.proton_mass();
+        if m_proton <= 0.0 || self.laws.G <= 0.0 { return 0.0; }
+        let mu_e = 2.0;
+        (k.h_bar * k.c / self.laws.G).powf(1.5) / (m_proton * mu_e).powi(2)
+    }
+
+    /// Margen del hidrógeno frente a la captura electrónica, `(m_n − m_p − m_e)`, relativo al de
+    /// nuestro universo y saturado en 1. Vale 0 si el protón o el hidrógeno no son estables.
+    pub fn atomic_stability_score(&self) -> Score {
+        let margin = self.laws.neutron_mass() - self.laws.proton_mass() - self.laws.mass_electron;
+        if margin <= 0.0 { return Score::ZERO; }
+        Score::new(margin / HYDROGEN_MARGIN_REAL)
+    }
+
+    /// Enlace electromagnético estable: un radio de Bohr atómico, modulado por la jerarquía de
+    /// leptones (si el electrón no es el leptón más ligero, decae y no hay química electrónica).
+    pub fn electromagnetic_score(&self) -> Score {
+        let bohr_radius = self.bohr_radius();
+        if bohr_radius > 0.0 && bohr_radius < 1e-9 {
+            profiled(ProfiledMethod::LeptonSpectrum, || self.lepton_spectrum_score())
+        } else {
+            Score::ZERO
+        }
+    }
+
+    /// Sub-puntuaciones del universo agrupadas por dominio físico, sin pesos.
+    pub fn summary(&self) -> EngineSummary {
+        EngineSummary {
+            chemistry: Score::new(0.5 * (*self.atomic_stability_score() + *self.electromagnetic_score())),
+            nuclear: self.nuclear_stability_score(),
+            stellar: Score::new(*self.calculate_stellar_viability() * *self.dark_matter_structure_score()),
+            heavy_elements: self.heavy_elements_viability(),
+            reproduction: self.calculate_black_hole_potential(),
+        }
+    }
+
+    /// Exponente del pico de Gamow de la reacción p + p, `τ = 3·(E_G / 4kT)^(1/3)`, con
+    /// `E_G = 2·m_r·c²·(π·α)²`. La tasa de fusión pp escala como `exp(-τ)`. Sin energía térmica
+    /// finita y positiva (p. ej. `t_core <= 0`) no hay exponente.
+    pub fn pp_gamow_exponent(&self, t_core: f64) -> Option<f64> {
+        gamow_exponent(&self.constants, self.laws.proton_mass(), self.alpha, t_core)
+    }
+
+    /// Compara el ritmo de fusión pp disponible con el que exige la gravedad, ambos relativos a
+    /// nuestro universo. La oferta escala como `exp(-τ)` (pico de Gamow); la demanda, como la
+    /// luminosidad de Eddington de una estrella de masa fija, `L ∝ G⁴·m_p⁴`. La puntuación es una
+    /// gaussiana en el logaritmo del cociente, de anchura `stellar_log_rate_tolerance`. Ambos ritmos
+    /// se evalúan a la temperatura central `stellar.core_temperature`.
+    pub fn calculate_stellar_viability(&self) -> Score {
+        let (Some(tau), Some(tau_ref)) = (
+            self.pp_gamow_exponent(self.stellar.core_temperature),
+            self.reference_gamow_exponent,
+        ) else {
+            return Score::ZERO;
+        };
+        if self.laws.G <= 0.0 { return Score::ZERO; }
+
+        let m_proton = self.laws.proton_mass();
+        let m_proton_ref = REAL_UNIVERSE.proton_mass();
+        let log_supply = tau_ref - tau;
+        let log_demand = EDDINGTON_LUMINOSITY_EXPONENT
+            * ((self.laws.G / REAL_UNIVERSE.G).ln() + (m_proton / m_proton_ref).ln());
+
+        let mismatch = (log_supply - log_demand) / self.tolerances.stellar_log_rate_tolerance;
+        Score::new((-0.5 * mismatch.powi(2)).exp())
+    }
+    
+    /// Formación de estructuras: la materia oscura colapsa en halos antes de la recombinación y
+    /// arrastra después a los bariones. Con muy poca, los halos se forman demasiado tarde para
+    /// albergar estrellas longevas; con demasiada, colapsan en halos tan densos que los encuentros
+    /// estelares desestabilizan los sistemas planetarios. La puntuación es una gaussiana en el
+    /// logaritmo del cociente materia oscura / bariónica relativo al de nuestro universo.
+    pub fn dark_matter_structure_score(&self) -> Score {
+        if self.laws.omega_dm <= 0.0 { return Score::ZERO; }
+        let log_ratio = ((self.laws.omega_dm / OMEGA_BARYON) / (REAL_OMEGA_DM / OMEGA_BARYON)).ln();
+        let sigma = self.tolerances.dark_matter_log_ratio_sigma;
+        Score::new((-0.5 * (log_ratio / sigma).powi(2)).exp())
+    }
+
+    /// Gaussiana (en décadas) de la masa de Chandrasekhar alrededor de la del progenitor de agujero
+    /// negro `stellar.black_hole_progenitor_mass`.
+    pub fn calculate_black_hole_potential(&self) -> Score {
+        let m_ch = self.chandrasekhar_mass();
+        let target_mass = self.stellar.black_hole_progenitor_mass * self.constants.m_solar;
+        if m_ch.is_nan() || m_ch.is_infinite() || m_ch <= 0.0 { return Score::ZERO; }
+        if !target_mass.is_finite() || target_mass <= 0.0 { return Score::ZERO; }
+        let target_log_mass = target_mass.log10();
+        let current_log_mass = m_ch.log10();
+        
+        let sigma = self.tolerances.black_hole_log_mass_sigma;
+        let exponent = -((current_log_mass - target_log_mass).powi(2)) / (2.0 * sigma.powi(2));
+        Score::new(exponent.exp())
+    }
+    
+    /// Energía de enlace del deuterón, modelada como un estado ligado de dos cuerpos
+    /// (análogo al átomo de Bohr) con acoplamiento `alpha_s` y la masa reducida protón-neutrón,
+    /// ajustado al deuterón real: `B = κ · ½ · alpha_s² · μ · c²` (`DEUTERON_BINDING_CALIBRATION`).
+    /// Sustituye al antiguo factor empírico `0.0023`.
+    pub fn deuteron_binding_energy(&self) -> f64 {
+        let m_proton = self.laws.proton_mass();
+        let m_neutron = self.laws.neutron_mass();
+        if m_proton <= 0.0 || m_neutron <= 0.0 { return 0.0; }
+        let m_reduced = m_proton * m_neutron / (m_proton + m_neutron);
+        DEUTERON_BINDING_CALIBRATION * 0.5 * self.laws.alpha_s.powi(2) * m_reduced * self.constants.c.powi(2)
+    }
+
+    /// Cuello de botella del deuterio: la puntuación es alta solo dentro de la ventana habitable.
+    ///
+    /// - Límite inferior: la primera reacción de la cadena pp (p + p → d + e⁺ + ν) debe ser
+    ///   exotérmica, es decir, `B_d > (m_n − m_p + m_e)·c²`. Si no, el deuterio no se forma y
+    ///   las estrellas no pueden quemar hidrógeno.
+    /// - Límite superior: si el enlace supera al real en más de `diproton_binding_limit`, el
+    ///   diprotón queda ligado y las estrellas consumen su hidrógeno casi instantáneamente.
+    ///
+    /// Cada límite es una sigmoide en escala logarítmica de anchura `nuclear_window_softness`, y la
+    /// ventana se modula por la jerarquía de quarks (`quark_spectrum_score`).
+    pub fn nuclear_stability_score(&self) -> Score {
+        let binding = self.deuteron_binding_energy();
+        if binding <= 0.0 { return Score::ZERO; }
+        let m_proton = self.laws.proton_mass();
+        let m_neutron = self.laws.neutron_mass();
+
+        let pp_threshold = (m_neutron - m_proton + self.laws.mass_electron) * self.constants.c.powi(2);
+        let lower = if pp_threshold <= 0.0 {
+            1.0
+        } else {
+            logistic((binding / pp_threshold).ln() / self.tolerances.nuclear_window_softness)
+        };
+
+        let diproton_limit = DEUTERON_BINDING_REAL * self.tolerances.diproton_binding_limit;
+        let upper = logistic((diproton_limit / binding).ln() / self.tolerances.nuclear_window_softness);
+
+        Score::new(lower * upper * *self.quark_spectrum_score())
+    }
+
+    /// Jerarquía de quarks por generaciones: si un quark de la segunda o tercera generación fuese
+    /// más ligero que su homólogo de la primera, los bariones más ligeros lo contendrían y el protón
+    /// y el neutrón decaerían a ellos. El deuterio exige `m_u < m_c < m_t` y `m_d < m_s < m_b`;
+    /// como en `lepton_spectrum_score`, cada par ordenado aporta una sigmoide en `ln(m_pesado / m_ligero)`.
+    pub fn quark_spectrum_score(&self) -> Score {
+        let laws = &self.laws;
+        let families = [
+            [laws.mass_up_quark, laws.mass_charm_quark, laws.mass_top_quark],
+            [laws.mass_down_quark, laws.mass_strange_quark, laws.mass_bottom_quark],
+        ];
+        if families.iter().flatten().any(|&m| m <= 0.0) { return Score::ZERO; }
+        let score = families.iter()
+            .flat_map(|family| family.windows(2))
+            .map(|pair| logistic((pair[1] / pair[0]).ln() / self.tolerances.quark_ordering_softness))
+            .product();
+        Score::new(score)
+    }
+
+    /// Jerarquía de leptones cargados: solo el leptón más ligero es estable y puede formar átomos,
+    /// así que la química electrónica exige `m_e < m_μ < m_τ`. Cada par ordenado aporta una
+    /// sigmoide en `ln(m_pesado / m_ligero)`; una inversión lleva la puntuación hacia 0.
+    pub fn lepton_spectrum_score(&self) -> Score {
+        let leptons = [self.laws.mass_electron, self.laws.mass_muon, self.laws.mass_tauon];
+        if leptons.iter().any(|&m| m <= 0.0) { return Score::ZERO; }
+        let score = leptons.windows(2)
+            .map(|pair| logistic((pair[1] / pair[0]).ln() / self.tolerances.lepton_ordering_softness))
+            .product();
+        Score::new(score)
+    }
+
+    // FUNCIÓN AÑADIDA QUE FALTABA
+    pub fn heavy_elements_viability(&self) -> Score {
+        let alpha_s_error = (self.laws.alpha_s - HEAVY_ELEMENTS_ALPHA_S_OPTIMAL).abs() / HEAVY_ELEMENTS_ALPHA_S_OPTIMAL;
+        let cutoff = self.tolerances.heavy_elements_alpha_s_cutoff;
+        
+        if alpha_s_error < cutoff {
+            Score::new(1.0 - alpha_s_error / (2.0 * cutoff))
+        } else {
+            Score::ZERO
+        }
+    }
+
+    /// Fitness medio de `samples` copias del universo con cada gen perturbado un ~1%
+    /// (`ROBUSTNESS_PERTURBATION`, log-normal). Premia las mesetas anchas frente a los picos
+    /// finamente ajustados; toda la aleatoriedad sale de `rng`, así que una semilla fija da un
+    /// resultado reproducible.
+    pub fn robustness_score(&self, weights: &FitnessWeights, samples: usize, rng: &mut impl Rng) -> f64 {
+        if samples == 0 { return 0.0; }
+        let genome = LogGenome::from_laws(&self.laws);
+        let total: f64 = (0..samples)
+            .map(|_| {
+                let perturbed = LogGenome(genome.0.map(|g| g + ROBUSTNESS_PERTURBATION * rng.sample::<f64, _>(StandardNormal)));
+                *calculate_fitness(&perturbed.to_laws(), weights).total
+            })
+            .sum();
+        total / samples as f64
+    }
+
+    /// Observables derivados del genoma, comparables entre universos.
+    pub fn observables(&self) -> [(&'static str, f64); 8] {
+        let laws = &self.laws;
+        let m_proton = laws.proton_mass();
+        let m_neutron = laws.neutron_mass();
+        [
+            ("alpha", self.alpha),
+            ("bohr_radius", self.bohr_radius()),
+            ("chandrasekhar_mass", self.chandrasekhar_mass()),
+            ("deuteron_binding", self.deuteron_binding_energy()),
+            ("proton_mass", m_proton),
+            ("neutron_proton_gap", m_neutron - m_proton),
+            ("muon_electron_ratio", laws.mass_muon / laws.mass_electron),
+            ("tau_muon_ratio", laws.mass_tauon / laws.mass_muon),
+        ]
+    }
+
+    /// Residuo relativo `|o - o_ref| / |o_ref|` de cada observable frente a `reference`.
+    /// Un residuo no finito se satura a `f64::MAX` para que siga siendo ordenable.
+    pub fn observable_residuals(&self, reference: &PhysicsEngine) -> Vec<(&'static str, f64)> {
+        self.observables().iter().zip(reference.observables())
+            .map(|(&(name, value), (_, expected))| {
+                let residual = ((value - expected) / expected).abs();
+                (name, if residual.is_finite() { residual } else { f64::MAX })
+            })
+            .collect()
+    }
+}
+
+/// Sub-puntuaciones en [0, 1] de cada dominio físico de la escalera de fitness. La química es la
+/// media del margen atómico y del enlace electromagnético; la estelar incluye la formación de estructuras.
+#[derive(Debug, Clone, Copy)]
+pub struct EngineSummary {
+    pub chemistry: Score,
+    pub nuclear: Score,
+    pub stellar: Score,
+    pub heavy_elements: Score,
+    pub reproduction: Score,
+}
+
+impl EngineSummary {
+    pub fn domains(&self) -> [(&'static str, Score); 5] {
+        [
+            ("Química", self.chemistry),
+            ("Nuclear", self.nuclear),
+            ("Estelar", self.stellar),
+            ("Elementos pesados", self.heavy_elements),
+            ("Reproducción", self.reproduction),
+        ]
+    }
+}
+
+// --- PESOS DE LA FUNCIÓN DE FITNESS ---
+/// Peso máximo de cada sub-puntuación en el fitness total.
+///
+/// Con los valores por defecto el máximo alcanzable es exactamente 1.0:
+/// química (0.1 atómica + 0.1 electromagnética) + nuclear 0.15 + estelar 0.2
+/// + elementos pesados 0.25 + reproductivo 0.2.
+///
+/// Las jerarquías de generaciones no tienen peso propio: la de leptones multiplica la puntuación
+/// electromagnética y la de quarks la nuclear, así que una inversión cuesta como mucho `electromagnetic`
+/// o `nuclear` y nunca eleva el máximo por encima de la suma de los pesos.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FitnessWeights {
+    pub atomic: f64,
+    pub electromagnetic: f64,
+    pub nuclear: f64,
+    pub stellar: f64,
+    pub heavy_elements: f64,
+    pub reproductive: f64,
+}
+
+impl Default for FitnessWeights {
+    fn default() -> Self {
+        Self { atomic: 0.1, electromagnetic: 0.1, nuclear: 0.15, stellar: 0.2, heavy_elements: 0.25, reproductive: 0.2 }
+    }
+}
+
+impl FitnessWeights {
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let weights: FitnessWeights = serde_json::from_str(&fs::read_to_string(path)?)?;
+        weights.validate()?;
+        Ok(weights)
+    }
+
+    pub fn as_array(&self) -> [f64; 6] {
+        [self.atomic, self.electromagnetic, self.nuclear, self.stellar, self.heavy_elements, self.reproductive]
+    }
+
+    pub fn from_array(w: [f64; 6]) -> Self {
+        Self { atomic: w[0], electromagnetic: w[1], nuclear: w[2], stellar: w[3], heavy_elements: w[4], reproductive: w[5] }
+    }
+
+    /// Fitness máximo alcanzable: cada sub-puntuación está acotada en [0, 1].
+    pub fn max_fitness(&self) -> f64 {
+        self.atomic + self.electromagnetic + self.nuclear + self.stellar + self.heavy_elements + self.reproductive
+    }
+
+    /// Comprueba que los pesos sean no negativos y que el máximo sea exactamente 1.0,
+    /// condición que asumen los umbrales de nivel (0.15, 0.4, 0.6, 0.75).
+    pub fn validate(&self) -> Result<(), String> {
+        let named = [
+            ("atomic", self.atomic), ("electromagnetic", self.electromagnetic), ("nuclear", self.nuclear),
+            ("stellar", self.stellar), ("heavy_elements", self.heavy_elements), ("reproductive", self.reproductive),
+        ];
+        for (name, weight) in named {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(format!("El peso '{}' debe ser finito y no negativo (valor: {})", name, weight));
+            }
+        }
+        let max = self.max_fitness();
+        if (max - 1.0).abs() > 1e-9 {
+            return Err(format!("Los pesos deben sumar 1.0 para mantener el fitness en [0, 1] (suman {})", max));
+        }
+        Ok(())
+    }
+}
+
+// --- REGISTRO ESTRUCTURADO (--json-logs) ---
+/// Con `--json-logs`, el progreso se emite como JSON por líneas en stderr y se silencian los mensajes legibles.
+pub static JSON_LOGS: AtomicBool = AtomicBool::new(false);
+
+/// `println!` para mensajes legibles de progreso, silenciado con `--json-logs`.
+#[macro_export]
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        if !$crate::JSON_LOGS.load(::std::sync::atomic::Ordering::Relaxed) {
+            println!($($arg)*);
+        }
+    };
+}
+
+// --- PERFILADO DEL MOTOR DE FÍSICA ---
+/// Métodos de puntuación instrumentados por `--profile`.
+#[derive(Debug, Clone, Copy)]
+enum ProfiledMethod {
+    LeptonSpectrum,
+    Nuclear,
+    Stellar,
+    HeavyElements,
+    BlackHole,
+}
+
+impl ProfiledMethod {
+    const ALL: [ProfiledMethod; 5] = [
+        ProfiledMethod::LeptonSpectrum, ProfiledMethod::Nuclear, ProfiledMethod::Stellar,
+        ProfiledMethod::HeavyElements, ProfiledMethod::BlackHole,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            ProfiledMethod::LeptonSpectrum => "lepton_spectrum_score",
+            ProfiledMethod::Nuclear => "nuclear_stability_score",
+            ProfiledMethod::Stellar => "calculate_stellar_viability",
+            ProfiledMethod::HeavyElements => "heavy_elements_viability",
+            ProfiledMethod::BlackHole => "calculate_black_hole_potential",
+        }
+    }
+}
+
+/// Interruptor global del perfilado: con él apagado, el coste es una lectura atómica por llamada.
+pub static PROFILING: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// Nanosegundos acumulados y número de llamadas por método, en el hilo actual.
+    static PROFILE: [Cell<(u64, u64)>; 5] = const { [const { Cell::new((0, 0)) }; 5] };
+}
+
+fn profiled<T>(method: ProfiledMethod, f: impl FnOnce() -> T) -> T {
+    if !PROFILING.load(Ordering::Relaxed) {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed().as_nanos() as u64;
+    PROFILE.with(|profile| {
+        let (nanos, calls) = profile[method as usize].get();
+        profile[method as usize].set((nanos + elapsed, calls + 1));
+    });
+    result
+}
+
+pub fn print_profile_report() {
+    let totals: Vec<(ProfiledMethod, u64, u64)> = PROFILE.with(|profile| {
+        ProfiledMethod::ALL.iter().map(|&m| {
+            let (nanos, calls) = profile[m as usize].get();
+            (m, nanos, calls)
+        }).collect()
+    });
+    let grand_total: u64 = totals.iter().map(|t| t.1).sum::<u64>().max(1);
+    progress!("--- PERFIL DEL MOTOR DE FÍSICA ---");
+    progress!("{:<32} {:>12} {:>14} {:>10} {:>8}", "método", "llamadas", "total (ms)", "ns/llamada", "%");
+    for (method, nanos, calls) in totals {
+        progress!(
+            "{:<32} {:>12} {:>14.3} {:>10.1} {:>7.1}%",
+            method.name(), calls, nanos as f64 / 1e6,
+            if calls > 0 { nanos as f64 / calls as f64 } else { 0.0 },
+            100.0 * nanos as f64 / grand_total as f64,
+        );
+    }
+}
+
+/// Nivel de complejidad máximo de la escalera de fitness.
+pub const MAX_COMPLEXITY_LEVEL: u8 = 4;
+
+/// Escalera de fitness completa: `total` y `level` son el fitness y el nivel de complejidad.
+pub fn calculate_fitness(laws: &CosmicLaw, weights: &FitnessWeights) -> FitnessReport {
+    match FITNESS_CACHE.get() {
+        Some(cache) => cache.get_or_compute(laws, weights),
+        None => calculate_fitness_up_to(laws, weights, MAX_COMPLEXITY_LEVEL),
+    }
+}
+
+// --- CACHÉ DE FITNESS ---
+/// Caché de `calculate_fitness` (`--cache-size`), compartida por todos los hilos.
+pub static FITNESS_CACHE: OnceLock<FitnessCache> = OnceLock::new();
+
+/// Caché LRU de evaluaciones indexada por la huella del genoma combinada con los pesos. Cada
+/// entrada guarda los bits exactos del genoma y de los pesos, y un acierto exige que coincidan:
+/// una colisión de huellas cuenta como fallo. No se cuantizan los genes, porque dos genomas casi
+/// iguales a ambos lados de una frontera de nivel compartirían un resultado incorrecto.
+pub struct FitnessCache {
+    state: Mutex<LruState>,
+}
+
+struct LruState {
+    capacity: usize,
+    entries: HashMap<u64, CacheEntry>,
+    /// Claves por instante de último uso; la primera es la menos reciente.
+    recency: BTreeMap<u64, u64>,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+struct CacheEntry {
+    genes: [u64; GENE_COUNT],
+    weights: [u64; 6],
+    result: FitnessReport,
+    last_used: u64,
+}
+
+impl FitnessCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            state: Mutex::new(LruState {
+                capacity,
+                entries: HashMap::with_capacity(capacity.min(1 << 20)),
+                recency: BTreeMap::new(),
+                clock: 0,
+                hits: 0,
+                misses: 0,
+            }),
+        }
+    }
+
+    pub fn get_or_compute(&self, laws: &CosmicLaw, weights: &FitnessWeights) -> FitnessReport {
+        let genes = laws.genes().map(f64::to_bits);
+        let weight_bits = weights.as_array().map(f64::to_bits);
+        let key = weight_bits.iter()
+            .fold(laws.fingerprint(), |hash, bits| (hash ^ bits).wrapping_mul(0x0000_0100_0000_01b3));
+
+        if let Some(result) = self.lock().lookup(key, &genes, &weight_bits) {
+            return result;
+        }
+        // La evaluación se hace sin el cerrojo para no serializar a los demás hilos
+        let result = calculate_fitness_up_to(laws, weights, MAX_COMPLEXITY_LEVEL);
+        self.lock().insert(key, CacheEntry { genes, weights: weight_bits, result, last_used: 0 });
+        result
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LruState> {
+        // Un pánico con el cerrojo tomado no deja la caché incoherente: cada operación es atómica
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn report(&self) {
+        let state = self.lock();
+        let total = state.hits + state.misses;
+        progress!(
+            "Caché de fitness: {} aciertos, {} fallos ({:.1}% de aciertos), {} entradas",
+            state.hits, state.misses,
+            if total > 0 { 100.0 * state.hits as f64 / total as f64 } else { 0.0 },
+            state.entries.len()
+        );
+    }
+}
+
+impl LruState {
+    fn lookup(&mut self, key: u64, genes: &[u64; GENE_COUNT], weights: &[u64; 6]) -> Option<FitnessReport> {
+        self.clock += 1;
+        let clock = self.clock;
+        match self.entries.get_mut(&key) {
+            Some(entry) if entry.genes == *genes && entry.weights == *weights => {
+                self.recency.remove(&entry.last_used);
+                entry.last_used = clock;
+                self.recency.insert(clock, key);
+                self.hits += 1;
+                Some(entry.result)
+            }
+            _ => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, key: u64, mut entry: CacheEntry) {
+        self.clock += 1;
+        entry.last_used = self.clock;
+        // Otro hilo pudo insertar la misma clave mientras evaluábamos; una colisión la reemplaza
+        if let Some(previous) = self.entries.remove(&key) {
+            self.recency.remove(&previous.last_used);
+        } else if self.entries.len() >= self.capacity {
+            if let Some((_, oldest)) = self.recency.pop_first() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.recency.insert(entry.last_used, key);
+        self.entries.insert(key, entry);
+    }
+}
+
+/// Igual que `calculate_fitness`, pero detiene la escalera al alcanzar `max_level`: las
+/// sub-puntuaciones de niveles superiores (las más caras) no se llegan a calcular.
+pub fn calculate_fitness_up_to(laws: &CosmicLaw, weights: &FitnessWeights, max_level: u8) -> FitnessReport {
+    evaluate_engine_ladder(&PhysicsEngine::new(laws.clone(), PhysicalConstants::default()), weights, max_level)
+}
+
+/// Primera barrera de la escalera de fitness que detuvo a un universo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LadderGate {
+    /// El protón no es más ligero que el neutrón.
+    ProtonHeavier,
+    /// El hidrógeno decae por captura electrónica (p + e⁻ → n + ν): m_p + m_e >= m_n.
+    HydrogenUnstable,
+    NoChemistry,
+    NoFusion,
+    NoHeavyElements,
+    NoReproduction,
+    /// La evaluación se detuvo en `max_level` sin fallar ninguna barrera.
+    LevelCap,
+    /// Universo auto-reproductivo: superó todas las barreras.
+    Complete,
+}
+
+impl LadderGate {
+    pub const ALL: [LadderGate; 8] = [
+        LadderGate::ProtonHeavier,
+        LadderGate::HydrogenUnstable,
+        LadderGate::NoChemistry,
+        LadderGate::NoFusion,
+        LadderGate::NoHeavyElements,
+        LadderGate::NoReproduction,
+        LadderGate::LevelCap,
+        LadderGate::Complete,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            LadderGate::ProtonHeavier => "protón más pesado que el neutrón",
+            LadderGate::HydrogenUnstable => "hidrógeno inestable (m_p + m_e >= m_n)",
+            LadderGate::NoChemistry => "sin química",
+            LadderGate::NoFusion => "sin fusión estelar",
+            LadderGate::NoHeavyElements => "sin carbono ni elementos pesados",
+            LadderGate::NoReproduction => "sin reproducción por agujeros negros",
+            LadderGate::LevelCap => "evaluación detenida por --max-level",
+            LadderGate::Complete => "ninguna (auto-reproductivo)",
+        }
+    }
+}
+
+// --- ESCALERA DE FITNESS ---
+/// Término ponderado de la escalera de fitness.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FitnessTerm {
+    pub name: &'static str,
+    /// Nivel de la escalera en el que se suma.
+    pub level: u8,
+    pub weight: f64,
+    /// Sub-puntuación; `None` si la escalera se detuvo antes de llegar a su nivel.
+    pub score: Option<Score>,
+    /// `weight · score`, o 0 si el término no se evaluó.
+    pub contribution: f64,
+}
+
+/// Resultado de la escalera de fitness: el total, el nivel alcanzado, la barrera que detuvo al
+/// universo (para que el mapeo pueda explicar por qué la mayoría son estériles) y cada término.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FitnessReport {
+    pub total: Fitness,
+    pub level: u8,
+    pub gate: LadderGate,
+    pub terms: [FitnessTerm; LADDER_TERMS.len()],
+}
+
+/// Definición de un término: su peso y la sub-puntuación del motor que pondera.
+struct LadderTerm {
+    name: &'static str,
+    level: u8,
+    weight: fn(&FitnessWeights) -> f64,
+    measure: fn(&PhysicsEngine) -> Score,
+}
+
+/// Términos de la escalera en orden de evaluación. Sin halos de materia oscura no hay dónde formar
+/// estrellas, así que la estructura modula la puntuación estelar.
+const LADDER_TERMS: [LadderTerm; 6] = [
+    LadderTerm { name: "atomic_stability", level: 1, weight: |w| w.atomic, measure: |e| e.atomic_stability_score() },
+    // Enlace electromagnético estable, modulado por la jerarquía de leptones
+    LadderTerm { name: "electromagnetic", level: 1, weight: |w| w.electromagnetic, measure: |e| e.electromagnetic_score() },
+    LadderTerm {
+        name: "nuclear_stability", level: 2, weight: |w| w.nuclear,
+        measure: |e| profiled(ProfiledMethod::Nuclear, || e.nuclear_stability_score()),
+    },
+    LadderTerm {
+        name: "stellar_viability", level: 2, weight: |w| w.stellar,
+        measure: |e| Score::new(*profiled(ProfiledMethod::Stellar, || e.calculate_stellar_viability()) * *e.dark_matter_structure_score()),
+    },
+    LadderTerm {
+        name: "heavy_elements", level: 3, weight: |w| w.heavy_elements,
+        measure: |e| profiled(ProfiledMethod::HeavyElements, || e.heavy_elements_viability()),
+    },
+    LadderTerm {
+        name: "black_hole_potential", level: 4, weight: |w| w.reproductive,
+        measure: |e| profiled(ProfiledMethod::BlackHole, || e.calculate_black_hole_potential()),
+    },
+];
+
+/// Umbral de fitness acumulado de cada nivel (1 a 4) y la barrera que se registra si no se alcanza:
+/// química básica, estrellas, química compleja y reproducción por agujeros negros.
+const LADDER_LEVELS: [(u8, f64, LadderGate); 4] = [
+    (1, 0.15, LadderGate::NoChemistry),
+    (2, 0.4, LadderGate::NoFusion),
+    (3, 0.6, LadderGate::NoHeavyElements),
+    (4, 0.75, LadderGate::NoReproduction),
+];
+
+/// Todas las sub-puntuaciones de la escalera, en el orden de `FitnessReport::terms`, sin detenerse
+/// en ninguna barrera (p. ej. para mostrar también las que no llegaron a contar).
+pub fn ladder_term_scores(engine: &PhysicsEngine) -> [Score; LADDER_TERMS.len()] {
+    LADDER_TERMS.each_ref().map(|term| (term.measure)(engine))
+}
+
+/// Escalera de fitness sobre un motor ya construido, p. ej. con otras tolerancias (`with_tolerances`).
+pub fn evaluate_engine_ladder(engine: &PhysicsEngine, weights: &FitnessWeights, max_level: u8) -> FitnessReport {
+    let laws = &engine.laws;
+    let mut report = FitnessReport {
+        total: Fitness::new(0.0),
+        level: 0,
+        gate: LadderGate::Complete,
+        terms: LADDER_TERMS.each_ref().map(|term| FitnessTerm {
+            name: term.name, level: term.level, weight: (term.weight)(weights), score: None, contribution: 0.0,
+        }),
+    };
+
+    // Verificación de viabilidad básica. Un mundo de neutrones (protón más pesado que el neutrón)
+    // también es estéril para la química electrónica, pero `classify_universe` lo distingue
+    if laws.proton_mass() >= laws.neutron_mass() {
+        report.gate = LadderGate::ProtonHeavier;
+        return report;
+    }
+    if laws.proton_mass() + laws.mass_electron >= laws.neutron_mass() {
+        report.gate = LadderGate::HydrogenUnstable;
+        return report;
+    }
+
+    climb_ladder(report, max_level, FitnessSum::new(), |term| (LADDER_TERMS[term].measure)(engine))
+}
+
+/// Sube los niveles de la escalera sumando las sub-puntuaciones de `score` (por índice en
+/// `LADDER_TERMS`) hasta la primera barrera o hasta `max_level`.
+fn climb_ladder(mut report: FitnessReport, max_level: u8, mut fitness: FitnessSum, score: impl Fn(usize) -> Score) -> FitnessReport {
+    for (level, threshold, gate) in LADDER_LEVELS {
+        // Los términos de un nivel se suman entre sí antes de añadirlos al total
+        let mut contributions = [0.0; LADDER_TERMS.len()];
+        let mut count = 0;
+        for (index, term) in report.terms.iter_mut().enumerate().filter(|(_, term)| term.level == level) {
+            let score = score(index);
+            term.score = Some(score);
+            term.contribution = term.weight * *score;
+            contributions[count] = term.contribution;
+            count += 1;
+        }
+        fitness.add_level(&contributions[..count]);
+        report.total = fitness.total();
+        if fitness.value() < threshold {
+            report.level = level - 1;
+            report.gate = gate;
+            return report;
+        }
+        // Con `max_level` se detiene tras superar el nivel, sin calcular los siguientes
+        if level < MAX_COMPLEXITY_LEVEL && max_level <= level {
+            report.level = max_level;
+            report.gate = LadderGate::LevelCap;
+            return report;
+        }
+    }
+
+    // Universo auto-reproductivo
+    report.level = MAX_COMPLEXITY_LEVEL;
+    report
+}
+
+/// Con `--kahan-sum`, `FitnessSum` usa suma compensada.
+pub static KAHAN_SUMMATION: AtomicBool = AtomicBool::new(false);
+
+/// Acumulador de las sub-puntuaciones ponderadas de la escalera de fitness. Los umbrales de nivel
+/// son comparaciones exactas sobre la suma, así que con `--kahan-sum` se arrastra el error de
+/// redondeo de cada suma (variante de Kahan–Babuška, correcta aunque un término supere a la suma)
+/// y el total es el redondeo de la suma exacta salvo en casos patológicos.
+struct FitnessSum {
+    sum: f64,
+    compensation: f64,
+    compensated: bool,
+}
+
+impl FitnessSum {
+    fn new() -> Self {
+        Self { sum: 0.0, compensation: 0.0, compensated: KAHAN_SUMMATION.load(Ordering::Relaxed) }
+    }
+
+    /// Suma la contribución de un nivel. Sin compensación, los términos del nivel se suman entre
+    /// sí antes de añadirlos al total, como siempre lo ha hecho la escalera.
+    fn add_level(&mut self, terms: &[f64]) {
+        if !self.compensated {
+            self.sum += terms.iter().sum::<f64>();
+            return;
+        }
+        for &term in terms {
+            let sum = self.sum + term;
+            self.compensation += if self.sum.abs() >= term.abs() {
+                (self.sum - sum) + term
+            } else {
+                (term - sum) + self.sum
+            };
+            self.sum = sum;
+        }
+    }
+
+    fn value(&self) -> f64 {
+        self.sum + self.compensation
+    }
+
+    fn total(&self) -> Fitness {
+        Fitness::new(self.value())
+    }
+}
+
+/// Mundo de neutrones: el protón es más pesado que el neutrón, así que los protones libres (o el
+/// hidrógeno, por captura electrónica) se convierten en neutrones. No hay química electrónica,
+/// pero la materia no desaparece: a diferencia de un universo muerto, queda materia neutrónica.
+pub fn is_neutron_world(laws: &CosmicLaw) -> bool {
+    laws.proton_mass() > laws.neutron_mass()
+}
+
+/// Como `analyze_universe_type`, pero separa los mundos de neutrones de los universos estériles.
+pub fn classify_universe(laws: &CosmicLaw, fitness: Fitness, level: u8) -> &'static str {
+    if *fitness <= 0.0 && is_neutron_world(laws) {
+        "Mundo de neutrones"
+    } else {
+        analyze_universe_type(level)
+    }
+}
+
+// Función auxiliar para análisis del paisaje
+pub fn analyze_universe_type(level: u8) -> &'static str {
+    match level {
+        0 => "Estéril",
+        1 => "Químico",
+        2 => "Estelar", 
+        3 => "Complejo",
+        4 => "Reproductivo",
+        _ => "Desconocido"
+    }
+}
+
+// --- IMPLEMENTACIÓN DE LA LÓGICA DE MUTACIÓN (CON HIPERMUTACIÓN) ---
+impl CosmicLaw {
+    /// Universo aleatorio, uniforme dentro del rango de muestreo de cada gen (`RangeConfig::active`).
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let ranges = RangeConfig::active();
+        let mut genes = [0.0; GENE_COUNT];
+        for gene in RANDOM_DRAW_ORDER {
+            let spec = ranges.spec(gene);
+            genes[gene as usize] = rng.gen_range(spec.min..spec.max);
+        }
+        Self::from_genes(genes)
+    }
+
+    /// Valores de todos los genes en un orden fijo.
+    pub fn genes(&self) -> [f64; GENE_COUNT] {
+        [
+            self.G, self.e, self.alpha_s, self.alpha_w,
+            self.mass_up_quark, self.mass_down_quark, self.mass_electron,
+            self.mass_charm_quark, self.mass_strange_quark, self.mass_muon,
+            self.mass_top_quark, self.mass_bottom_quark, self.mass_tauon,
+            self.omega_dm,
+        ]
+    }
+
+    /// Construye un genoma a partir de los genes en el orden de `genes`.
+    pub fn from_genes(genes: [f64; GENE_COUNT]) -> Self {
+        let [g, e, alpha_s, alpha_w, mass_up_quark, mass_down_quark, mass_electron,
+            mass_charm_quark, mass_strange_quark, mass_muon,
+            mass_top_quark, mass_bottom_quark, mass_tauon, omega_dm] = genes;
+        Self {
+            G: g, e, alpha_s, alpha_w, mass_up_quark, mass_down_quark, mass_electron,
+            mass_charm_quark, mass_strange_quark, mass_muon,
+            mass_top_quark, mass_bottom_quark, mass_tauon, omega_dm,
+        }
+    }
+
+    /// Lee un genoma de un archivo JSON y lo valida.
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let laws: CosmicLaw = serde_json::from_str(&fs::read_to_string(path)?)?;
+        laws.validate().map_err(|e| format!("{}: {}", path, e))?;
+        Ok(laws)
+    }
+
+    /// Todos los genes (masas, acoplamientos y `omega_dm`) deben ser finitos y estrictamente
+    /// positivos: un cero o un negativo no tiene sentido físico, anula todas las puntuaciones y no
+    /// tiene logaritmo, así que el genoma no podría evolucionar en espacio log-genético.
+    pub fn validate(&self) -> Result<(), String> {
+        for (value, spec) in self.genes().iter().zip(GENES.iter()) {
+            if !value.is_finite() || *value <= 0.0 {
+                return Err(format!("el gen '{}' debe ser finito y mayor que 0 (es {})", spec.name, value));
+            }
+        }
+        Ok(())
+    }
+
+    /// Huella del genoma: hash FNV-1a de la representación binaria exacta de todos los genes.
+    /// Dos genomas tienen la misma huella si y solo si (salvo colisión) son idénticos bit a bit.
+    pub fn fingerprint(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+        self.genes().iter()
+            .flat_map(|gene| gene.to_bits().to_le_bytes())
+            .fold(FNV_OFFSET, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+    }
+
+    /// Valor del gen `gene`.
+    pub fn gene(&self, gene: Gene) -> f64 {
+        self.genes()[gene as usize]
+    }
+
+    /// Referencia mutable al campo correspondiente a `gene`.
+    pub fn gene_mut(&mut self, gene: Gene) -> &mut f64 {
+        match gene {
+            Gene::G => &mut self.G,
+            Gene::E => &mut self.e,
+            Gene::AlphaS => &mut self.alpha_s,
+            Gene::AlphaW => &mut self.alpha_w,
+            Gene::MassUpQuark => &mut self.mass_up_quark,
+            Gene::MassDownQuark => &mut self.mass_down_quark,
+            Gene::MassElectron => &mut self.mass_electron,
+            Gene::MassCharmQuark => &mut self.mass_charm_quark,
+            Gene::MassStrangeQuark => &mut self.mass_strange_quark,
+            Gene::MassMuon => &mut self.mass_muon,
+            Gene::MassTopQuark => &mut self.mass_top_quark,
+            Gene::MassBottomQuark => &mut self.mass_bottom_quark,
+            Gene::MassTauon => &mut self.mass_tauon,
+            Gene::OmegaDm => &mut self.omega_dm,
+        }
+    }
+
+    /// Copia del genoma con `gene` sustituido por `value`.
+    pub fn with_gene(&self, gene: Gene, value: f64) -> Self {
+        let mut laws = self.clone();
+        *laws.gene_mut(gene) = value;
+        laws
+    }
+
+    /// Masa del protón (`uud`): la parte fuerte común a los nucleones, sus quarks de valencia y su
+    /// autoenergía electromagnética, que escala como `e²`.
+    pub fn proton_mass(&self) -> f64 {
+        NUCLEON_QCD_MASS + 2.0 * self.mass_up_quark + self.mass_down_quark
+            + PROTON_EM_MASS_REAL * (self.e / REAL_UNIVERSE.e).powi(2)
+    }
+
+    /// Masa del neutrón (`udd`): la parte fuerte común a los nucleones y sus quarks de valencia.
+    pub fn neutron_mass(&self) -> f64 {
+        NUCLEON_QCD_MASS + self.mass_up_quark + 2.0 * self.mass_down_quark
+    }
+
+    /// Distancia euclídea en espacio log-genético: todas las constantes abarcan
+    /// muchos órdenes de magnitud, así que se comparan sus logaritmos.
+    pub fn log_distance(&self, other: &CosmicLaw) -> f64 {
+        LogGenome::from_laws(self).distance(&LogGenome::from_laws(other))
+    }
+}
+
+// --- REPRESENTACIÓN LOG-GENÉTICA ---
+/// Genoma en espacio logarítmico: el logaritmo natural de cada gen, en el orden de `GENES`.
+/// Las constantes abarcan muchos órdenes de magnitud y varían de forma multiplicativa, así que
+/// la evolución, las distancias y el PSO operan aquí; la física se evalúa tras `to_laws`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LogGenome(pub [f64; GENE_COUNT]);
+
+/// Peso de cada gen en la distancia log-genética, en el orden de `GENES`. Permite restar
+/// importancia a las dimensiones que no interesan en un análisis (diversidad, agrupamiento,
+/// cuencas de atracción, distancia al objetivo).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct GeneWeights(pub [f64; GENE_COUNT]);
+
+/// Pesos por gen activos en el proceso, fijados una vez al arrancar desde `--gene-weights`.
+pub static GENE_WEIGHTS: OnceLock<GeneWeights> = OnceLock::new();
+
+impl GeneWeights {
+    pub const UNIFORM: GeneWeights = GeneWeights([1.0; GENE_COUNT]);
+
+    /// Lee un objeto JSON `{"nombre_del_gen": peso, ...}`; los genes ausentes pesan 1.0.
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let named: std::collections::HashMap<String, f64> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let mut weights = Self::UNIFORM;
+        for (name, weight) in named {
+            let index = GENES.iter().position(|spec| spec.name == name)
+                .ok_or_else(|| format!("Gen desconocido en {}: '{}'", path, name))?;
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(format!("El peso del gen '{}' debe ser finito y no negativo (valor: {})", name, weight).into());
+            }
+            weights.0[index] = weight;
+        }
+        Ok(weights)
+    }
+
+    pub fn active() -> &'static GeneWeights {
+        GENE_WEIGHTS.get().unwrap_or(&Self::UNIFORM)
+    }
+}
+
+/// Genes que deciden juntos la viabilidad del hidrógeno (`m_p + m_e < m_n`, con las masas de
+/// `CosmicLaw::proton_mass` y `neutron_mass`): la diferencia `m_n − m_p` depende de `m_d − m_u` y de
+/// la autoenergía electromagnética del protón, que escala como `e²`. La recombinación los hereda
+/// siempre juntos.
+const HYDROGEN_LINKAGE: [Gene; 4] = [Gene::E, Gene::MassUpQuark, Gene::MassDownQuark, Gene::MassElectron];
+
+/// Paso local de la mutación, aplicado en espacio logarítmico.
+#[derive(Debug, Clone, Copy)]
+pub enum MutationKernel {
+    /// Factor uniforme en [0.95, 1.05), es decir, `ln(factor)` sumado al logaritmo del gen.
+    Multiplicative,
+    /// Paso gaussiano de la desviación típica dada (en unidades de logaritmo natural).
+    Gaussian(f64),
+}
+
+impl LogGenome {
+    pub fn from_laws(laws: &CosmicLaw) -> Self {
+        Self(laws.genes().map(f64::ln))
+    }
+
+    pub fn to_laws(self) -> CosmicLaw {
+        CosmicLaw::from_genes(self.0.map(f64::exp))
+    }
+
+    /// Distancia euclídea entre dos genomas logarítmicos, ponderada por los pesos por gen activos
+    /// (`--gene-weights`; uniformes por defecto).
+    pub fn distance(&self, other: &LogGenome) -> f64 {
+        self.weighted_distance(other, GeneWeights::active())
+    }
+
+    pub fn weighted_distance(&self, other: &LogGenome, weights: &GeneWeights) -> f64 {
+        self.0.iter().zip(&other.0).zip(&weights.0)
+            .map(|((a, b), w)| w * (a - b).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Aplica una mutación a una copia del genoma, con posibilidad de hipermutación: cada gen muta con
+    /// probabilidad `rate` y, de hacerlo, salta con probabilidad `hypermutation_chance` a un valor
+    /// uniforme de su rango de muestreo (`RangeConfig::active`) en lugar de dar un paso local.
+    pub fn mutate(&self, rng: &mut impl Rng, rate: f64, hypermutation_chance: f64, kernel: MutationKernel) -> Self {
+        let mut child = *self;
+        for (value, spec) in child.0.iter_mut().zip(RangeConfig::active().0.iter()) {
+            if rng.gen::<f64>() >= rate {
+                continue;
+            }
+            *value = if rng.gen::<f64>() < hypermutation_chance {
+                rng.gen_range(spec.min..spec.max).ln()
+            } else {
+                match kernel {
+                    MutationKernel::Multiplicative => *value + rng.gen_range(0.95f64..1.05).ln(),
+                    MutationKernel::Gaussian(sigma) => *value + sigma * rng.sample::<f64, _>(StandardNormal),
+                }
+            };
+        }
+        child
+    }
+
+    /// Recombinación uniforme: cada gen se hereda de uno de los dos padres al azar o, con `blend`,
+    /// se interpola entre ambos con un peso aleatorio (en escala logarítmica, es decir, una media
+    /// geométrica ponderada). Los genes de `HYDROGEN_LINKAGE` se heredan en bloque: mezclarlos
+    /// rompería con facilidad `m_p + m_e < m_n` aunque ambos padres la cumplan.
+    pub fn crossover(&self, other: &LogGenome, blend: bool, rng: &mut impl Rng) -> Self {
+        let linkage_draw: f64 = rng.gen();
+        let mut child = *self;
+        for gene in Gene::ALL {
+            let draw = if HYDROGEN_LINKAGE.contains(&gene) { linkage_draw } else { rng.gen() };
+            let i = gene as usize;
+            child.0[i] = match (blend, draw < 0.5) {
+                (true, _) => draw * self.0[i] + (1.0 - draw) * other.0[i],
+                (false, true) => self.0[i],
+                (false, false) => other.0[i],
+            };
+        }
+        child
+    }
+
+    /// Macromutación: intercambia los valores de dos genes elegidos al azar dentro de una misma
+    /// familia intercambiable, explorando permutaciones de la jerarquía de masas.
+    pub fn swap_genes(&self, rng: &mut impl Rng) -> Self {
+        let category = *GeneCategory::SWAPPABLE.choose(rng).unwrap();
+        let members: Vec<Gene> = category.genes().collect();
+        let pair = rand::seq::index::sample(rng, members.len(), 2);
+        let mut child = *self;
+        child.0.swap(members[pair.index(0)] as usize, members[pair.index(1)] as usize);
+        child
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn real_universe_is_self_reproducing() {
+        let report = calculate_fitness_up_to(&REAL_UNIVERSE, &FitnessWeights::default(), MAX_COMPLEXITY_LEVEL);
+        assert!(*report.total > 0.0);
+        assert_eq!((report.level, report.gate), (MAX_COMPLEXITY_LEVEL, LadderGate::Complete));
+    }
+
+    #[test]
+    fn hydrogen_gate_rejects_electron_capture() {
+        let weights = FitnessWeights::default();
+        let margin = REAL_UNIVERSE.neutron_mass() - REAL_UNIVERSE.proton_mass() - REAL_UNIVERSE.mass_electron;
+        assert!(margin > 0.0);
+        assert_ne!(calculate_fitness_up_to(&REAL_UNIVERSE, &weights, MAX_COMPLEXITY_LEVEL).gate, LadderGate::HydrogenUnstable);
+
+        // Un electrón más pesado que el margen: el protón sigue siendo más ligero que el neutrón,
+        // pero el hidrógeno se convierte en neutrón por captura electrónica
+        let capturing = REAL_UNIVERSE.with_gene(Gene::MassElectron, REAL_UNIVERSE.mass_electron + 2.0 * margin);
+        assert!(capturing.proton_mass() < capturing.neutron_mass());
+        assert!(capturing.proton_mass() + capturing.mass_electron > capturing.neutron_mass());
+        let report = calculate_fitness_up_to(&capturing, &weights, MAX_COMPLEXITY_LEVEL);
+        assert_eq!((report.level, report.gate), (0, LadderGate::HydrogenUnstable));
+        assert_eq!(*report.total, 0.0);
+    }
+
+    #[test]
+    fn real_universe_reproduces_codata_nucleon_masses() {
+        assert!((REAL_UNIVERSE.proton_mass() / PROTON_MASS_REAL - 1.0).abs() < 1e-12);
+        assert!((REAL_UNIVERSE.neutron_mass() / NEUTRON_MASS_REAL - 1.0).abs() < 1e-12);
+        let binding = PhysicsEngine::from_real_universe().deuteron_binding_energy();
+        assert!((binding / DEUTERON_BINDING_REAL - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn kahan_summation_reaches_a_threshold_that_naive_summation_misses() {
+        // Sub-puntuaciones con los pesos por defecto cuya suma ponderada exacta hasta el nivel 3 es
+        // justo el umbral 0.6; sumada nivel a nivel sin compensación queda en 0.5999999999999999
+        const SCORES: [f64; 6] = [
+            0.9587284295536277, 0.8428617932030641, 0.6079461665668856,
+            0.9008299265853187, 0.5939322696889369, 0.23929301543907866,
+        ];
+        let weights = FitnessWeights::default();
+        let climb = |compensated| {
+            let report = FitnessReport {
+                total: Fitness::new(0.0),
+                level: 0,
+                gate: LadderGate::Complete,
+                terms: LADDER_TERMS.each_ref().map(|term| FitnessTerm {
+                    name: term.name, level: term.level, weight: (term.weight)(&weights), score: None, contribution: 0.0,
+                }),
+            };
+            let fitness = FitnessSum { sum: 0.0, compensation: 0.0, compensated };
+            climb_ladder(report, 3, fitness, |term| Score::new(SCORES[term]))
+        };
+        let (naive, compensated) = (climb(false), climb(true));
+
+        // Referencia exacta: todas las contribuciones son múltiplos enteros de 2⁻⁶⁴
+        let scale = 2f64.powi(64);
+        let exact: i128 = compensated.terms.iter()
+            .filter(|term| term.level <= 3)
+            .map(|term| {
+                let scaled = term.contribution * scale;
+                assert_eq!(scaled.fract(), 0.0);
+                scaled as i128
+            })
+            .sum();
+        assert_eq!(exact, (0.6 * scale) as i128);
+
+        assert_eq!((naive.level, naive.gate), (2, LadderGate::NoHeavyElements));
+        assert!(*naive.total < 0.6);
+        assert_eq!((compensated.level, compensated.gate), (3, LadderGate::LevelCap));
+        assert_eq!(*compensated.total, 0.6);
+    }
+
+    #[test]
+    fn genomes_survive_json_round_trip_bit_for_bit() {
+        let mut rng = StdRng::seed_from_u64(416);
+        let path = std::env::temp_dir().join(format!("rust_universe_round_trip_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        for i in 0..2000 {
+            // La mitad de los genomas usa bits aleatorios (exponentes extremos, subnormales) en
+            // lugar del rango de muestreo
+            let laws = if i % 2 == 0 {
+                CosmicLaw::random(&mut rng)
+            } else {
+                CosmicLaw::from_genes(std::array::from_fn(|_| loop {
+                    let value = f64::from_bits(rng.gen::<u64>() >> 1);
+                    if value.is_finite() && value > 0.0 {
+                        break value;
+                    }
+                }))
+            };
+            fs::write(path, serde_json::to_string_pretty(&laws).unwrap()).unwrap();
+            let loaded = CosmicLaw::load(path).unwrap();
+            let bits = |laws: &CosmicLaw| laws.genes().map(f64::to_bits);
+            assert_eq!(bits(&loaded), bits(&laws), "{:?}", laws);
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
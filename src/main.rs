@@ -1,473 +1,4969 @@
 use std::error::Error;
 use rand::prelude::*;
 use rand::Rng;
-use std::f64::consts::PI;
+use rand_chacha::ChaCha12Rng;
+use rand_distr::{Binomial, Distribution, StandardNormal};
+use std::fmt::Write as _;
+use std::io::{IsTerminal, Write as _};
 use std::fs;
-use serde::Deserialize;
-use clap::{Parser, Subcommand};
-
-// --- LEVEL 0: CONSTANTES FÍSICAS INMUTABLES ---
-const C: f64 = 299_792_458.0;
-const H_BAR: f64 = 1.054_571_817e-34;
-const EPSILON_0: f64 = 8.854_187_81e-12;
-const K_B: f64 = 1.380649e-23; // Constante de Boltzmann
-const M_SOLAR: f64 = 1.989e30;
-
-// --- LEVEL 1: PLANTILLAS DE PARTÍCULAS ---
-#[derive(Debug, Clone)]
-struct ParticleBlueprint {
-    name: &'static str,
-    spin: f64,
-}
-
-lazy_static::lazy_static! {
-    // 1st Gen
-    static ref UP_QUARK: ParticleBlueprint = ParticleBlueprint { name: "Up", spin: 0.5 };
-    static ref DOWN_QUARK: ParticleBlueprint = ParticleBlueprint { name: "Down", spin: 0.5 };
-    static ref ELECTRON: ParticleBlueprint = ParticleBlueprint { name: "Electron", spin: 0.5 };
-    // 2nd Gen
-    static ref CHARM_QUARK: ParticleBlueprint = ParticleBlueprint { name: "Charm", spin: 0.5 };
-    static ref STRANGE_QUARK: ParticleBlueprint = ParticleBlueprint { name: "Strange", spin: 0.5 };
-    static ref MUON: ParticleBlueprint = ParticleBlueprint { name: "Muon", spin: 0.5 };
-    // 3rd Gen
-    static ref TOP_QUARK: ParticleBlueprint = ParticleBlueprint { name: "Top", spin: 0.5 };
-    static ref BOTTOM_QUARK: ParticleBlueprint = ParticleBlueprint { name: "Bottom", spin: 0.5 };
-    static ref TAUON: ParticleBlueprint = ParticleBlueprint { name: "Tauon", spin: 0.5 };
-}
-
-// --- LEVEL 2: EL GENOMA DE UN UNIVERSO ---
-#[derive(Debug, Clone, Deserialize)] 
-struct CosmicLaw {
-    G: f64,
-    e: f64,
-    alpha_s: f64,
-    alpha_w: f64,
-    mass_up_quark: f64, mass_down_quark: f64, mass_electron: f64,
-    mass_charm_quark: f64, mass_strange_quark: f64, mass_muon: f64,
-    mass_top_quark: f64, mass_bottom_quark: f64, mass_tauon: f64,
-}
-
-// --- MOTOR DE FÍSICA ---
-#[derive(Debug)]
-struct PhysicsEngine {
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, VecDeque};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use indicatif::{ProgressBar, ProgressState, ProgressStyle};
+use rust_universe::*;
+
+// --- REGISTRO ESTRUCTURADO (--json-logs) ---
+/// Versión del esquema de `ProgressEvent`. Añadir campos no la cambia; renombrar o quitar campos, sí.
+const JSON_LOG_SCHEMA_VERSION: u32 = 1;
+
+/// Evento de progreso: uno por generación (modo evolutivo) o por hito de mapeo. Los campos que no
+/// aplican a un modo se emiten como `null`, de modo que todas las líneas tienen las mismas claves.
+#[derive(Debug, Serialize)]
+struct ProgressEvent {
+    schema_version: u32,
+    /// Segundos desde la época Unix.
+    timestamp: f64,
+    /// `"generation"` o `"mapping"`.
+    event: &'static str,
+    /// Generación, o número de universos mapeados.
+    iteration: u64,
+    best_fitness: f64,
+    /// Distancia log-genética media al centroide de la población.
+    diversity: Option<f64>,
+    mutation_rate: Option<f64>,
+}
+
+impl ProgressEvent {
+    fn new(event: &'static str, iteration: u64, best_fitness: f64) -> Self {
+        Self {
+            schema_version: JSON_LOG_SCHEMA_VERSION,
+            timestamp: unix_timestamp(),
+            event,
+            iteration,
+            best_fitness,
+            diversity: None,
+            mutation_rate: None,
+        }
+    }
+
+    fn with_diversity(mut self, diversity: f64) -> Self {
+        self.diversity = Some(diversity);
+        self
+    }
+
+    fn with_mutation_rate(mut self, mutation_rate: f64) -> Self {
+        self.mutation_rate = Some(mutation_rate);
+        self
+    }
+
+    /// Escribe el evento en stderr si `--json-logs` está activo.
+    fn emit(&self) {
+        if JSON_LOGS.load(Ordering::Relaxed) {
+            if let Ok(line) = serde_json::to_string(self) {
+                eprintln!("{}", line);
+            }
+        }
+    }
+}
+
+// --- BARRAS DE PROGRESO ---
+/// Barras de progreso activas: sin `--quiet` ni `--json-logs` y con la salida estándar en una terminal.
+static PROGRESS_BARS: AtomicBool = AtomicBool::new(false);
+
+const MAP_BAR_TEMPLATE: &str = "{bar:30} {human_pos}/{human_len} universos ({rate}, ETA {eta})";
+const EVOLVE_BAR_TEMPLATE: &str = "{bar:30} {pos}/{len} generaciones (ETA {eta}) {msg}";
+
+/// Barra de `len` pasos con la plantilla dada, u oculta si las barras están desactivadas. Los
+/// mensajes que se impriman mientras avanza deben pasar por `ProgressBar::suspend` para que el
+/// redibujado no los parta.
+fn progress_bar(len: u64, template: &str) -> ProgressBar {
+    if !PROGRESS_BARS.load(Ordering::Relaxed) {
+        return ProgressBar::hidden();
+    }
+    let bar = ProgressBar::new(len);
+    if let Ok(style) = ProgressStyle::with_template(template) {
+        // Pasos por segundo sin decimales (`{per_sec}` muestra cuatro)
+        bar.set_style(style.with_key("rate", |state: &ProgressState, w: &mut dyn std::fmt::Write| {
+            let _ = write!(w, "{:.0}/s", state.per_sec());
+        }));
+    }
+    bar
+}
+
+/// Segundos desde la época Unix.
+fn unix_timestamp() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |d| d.as_secs_f64())
+}
+
+/// Distancia euclídea media, en espacio log-genético, de cada genoma al centroide de la población.
+fn population_diversity(log_population: &[LogGenome]) -> f64 {
+    if log_population.is_empty() { return 0.0; }
+    let n = log_population.len() as f64;
+    let centroid = LogGenome(std::array::from_fn(|d| log_population.iter().map(|g| g.0[d]).sum::<f64>() / n));
+    log_population.iter().map(|g| g.distance(&centroid)).sum::<f64>() / n
+}
+
+// --- PERFILADO DE MEMORIA ---
+/// Asignador global que delega en el del sistema y lleva la cuenta de los bytes en uso y de su
+/// máximo. El coste es un par de operaciones atómicas por asignación.
+struct CountingAllocator;
+
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+#[global_allocator]
+static GLOBAL_ALLOCATOR: CountingAllocator = CountingAllocator;
+
+impl CountingAllocator {
+    fn record_growth(bytes: usize) {
+        let now = ALLOCATED_BYTES.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        PEAK_ALLOCATED_BYTES.fetch_max(now, Ordering::Relaxed);
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            Self::record_growth(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            if new_size >= layout.size() {
+                Self::record_growth(new_size - layout.size());
+            } else {
+                ALLOCATED_BYTES.fetch_sub(layout.size() - new_size, Ordering::Relaxed);
+            }
+        }
+        new_ptr
+    }
+}
+
+fn print_memory_report(label: &str) {
+    let mebibytes = |bytes: usize| bytes as f64 / (1024.0 * 1024.0);
+    progress!(
+        "{}: {:.2} MiB en uso, pico {:.2} MiB",
+        label,
+        mebibytes(ALLOCATED_BYTES.load(Ordering::Relaxed)),
+        mebibytes(PEAK_ALLOCATED_BYTES.load(Ordering::Relaxed)),
+    );
+}
+
+// --- DEFINICIÓN DE LA INTERFAZ DE LÍNEA DE COMANDOS (CLI) ---
+#[derive(Parser)]
+#[command(author, version, about = "Simulador Cosmológico 'El Armónico 137'", long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+    /// Emite el progreso como eventos JSON por líneas en stderr y silencia los mensajes legibles.
+    #[arg(long, global = true)]
+    json_logs: bool,
+    /// Archivo JSON con las tolerancias de las puntuaciones (`ToleranceConfig`).
+    #[arg(long, global = true)]
+    tolerances: Option<String>,
+    /// Archivo JSON con el peso de cada gen en la distancia log-genética (`{"G": 0.5, ...}`).
+    #[arg(long, global = true)]
+    gene_weights: Option<String>,
+    /// Archivo JSON con los valores de referencia del modelo estelar (`StellarParams`): temperatura
+    /// central de la fusión pp y masa del progenitor de agujero negro; por defecto, los solares.
+    #[arg(long, global = true)]
+    stellar_config: Option<String>,
+    /// Archivo JSON con los rangos de muestreo e hipermutación de cada gen (`{"G": {"min": ..., "max": ...}, ...}`);
+    /// los genes ausentes conservan su rango por defecto.
+    #[arg(long, global = true)]
+    ranges: Option<String>,
+    /// Guarda en memoria hasta N evaluaciones de fitness (LRU) para no repetir las de genomas ya vistos.
+    #[arg(long, global = true, value_parser = parse_count)]
+    cache_size: Option<usize>,
+    /// Texto que se escribe en los CSV en lugar de un valor no finito (NaN o infinito); por defecto, una celda vacía.
+    #[arg(long, global = true)]
+    non_finite_token: Option<String>,
+    /// Acumula las sub-puntuaciones del fitness con suma compensada (Kahan–Babuška) para que los
+    /// umbrales de nivel no dependan del redondeo.
+    #[arg(long, global = true)]
+    kahan_sum: bool,
+    /// Oculta las barras de progreso de `map` y `evolve`. Tampoco se muestran con `--json-logs` ni
+    /// si la salida estándar no es una terminal.
+    #[arg(long, global = true)]
+    quiet: bool,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Modo Mapeo: Simula N universos aleatorios para encontrar candidatos viables.
+    Map(MapArgs),
+    /// Modo Evolutivo: Evoluciona una población a partir de una semilla.
+    Evolve(Box<EvolveArgs>),
+    /// Volumen habitable: fracción del rango de cada gen que mantiene el universo viable, con el resto fijo.
+    HabitableVolume {
+        /// Genoma de referencia (JSON) que fija los genes no barridos.
+        #[arg(short, long)]
+        genome: String,
+        /// Puntos del barrido por gen (espaciados logarítmicamente).
+        #[arg(long, default_value_t = 200, value_parser = clap::value_parser!(u32).range(2..))]
+        steps: u32,
+        /// Nivel de complejidad mínimo para considerar un punto habitable.
+        #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u8).range(0..=4))]
+        min_level: u8,
+        /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+        #[arg(long)]
+        weights: Option<String>,
+    },
+    /// Calibración: ajusta los `FitnessWeights` para maximizar el fitness de nuestro universo.
+    Calibrate {
+        /// Genoma de referencia (JSON); por defecto, nuestro universo.
+        #[arg(short, long)]
+        genome: Option<String>,
+        /// Iteraciones del escalador de colinas.
+        #[arg(long, default_value_t = 5_000, value_parser = clap::value_parser!(u32).range(1..))]
+        iterations: u32,
+        /// Archivo donde se escriben los pesos calibrados.
+        #[arg(short, long, default_value = "calibrated_weights.json")]
+        output: String,
+    },
+    /// Matriz de distancias: distancias log-genéticas entre todos los pares de un arreglo JSON de genomas.
+    DistanceMatrix {
+        /// Archivo JSON con un arreglo de `CosmicLaw`.
+        #[arg(short, long)]
+        input: String,
+        /// CSV de salida con la matriz NxN (legible por `scipy.cluster.hierarchy.linkage` vía `squareform`).
+        #[arg(short, long, default_value = "distance_matrix.csv")]
+        output: String,
+    },
+    /// Intervalos de confianza bootstrap de la fracción viable y de la fracción por nivel de un mapeo.
+    BootstrapCi {
+        /// Recuento de resultados escrito por `map --outcomes`.
+        #[arg(short, long)]
+        input: String,
+        /// Número de remuestreos bootstrap.
+        #[arg(long, default_value_t = 2_000, value_parser = clap::value_parser!(u32).range(1..))]
+        resamples: u32,
+        /// Nivel de confianza del intervalo.
+        #[arg(long, default_value_t = 0.95, value_parser = parse_open_unit_interval)]
+        confidence: f64,
+    },
+    /// Fracción viable esperada bajo una medida a priori sobre las constantes, reponderando por
+    /// muestreo de importancia los universos viables de un mapeo uniforme.
+    ExpectedViable {
+        /// Recuento de resultados escrito por `map --outcomes`.
+        #[arg(long)]
+        outcomes: String,
+        /// CSV del paisaje del mismo mapeo, con el genoma completo (`map` sin `--compat-csv`).
+        #[arg(short, long, default_value = "landscape_data.csv")]
+        input: String,
+        /// Medida a priori (JSON): por gen, `{"distribution": "uniform" | "log-uniform", "min", "max"}`.
+        /// Los genes omitidos conservan la distribución uniforme del mapeo.
+        #[arg(long)]
+        prior: String,
+        /// Nivel de complejidad mínimo para contar un universo como viable.
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=4))]
+        min_level: u8,
+        /// Tamaño del conjunto de universos; si se indica, se imprime además el número esperado de viables.
+        #[arg(long)]
+        universes: Option<f64>,
+    },
+    /// Repite el experimento de un manifiesto (`map` o `evolve` con `--write-manifest`) y comprueba
+    /// que las salidas coinciden byte a byte. Las salidas se reescriben en sus rutas originales.
+    Reproduce {
+        /// Manifiesto del experimento.
+        manifest: String,
+    },
+    /// Reconstruye un universo de un mapeo uniforme a partir de la semilla base y su índice.
+    Replay {
+        /// Semilla base impresa por `map`.
+        #[arg(long)]
+        seed: u64,
+        /// Índice del universo dentro del mapeo.
+        #[arg(long)]
+        index: u64,
+        /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+        #[arg(long)]
+        weights: Option<String>,
+    },
+    /// Cuenca de atracción: fracción de arranques cercanos desde los que el escalador de colinas
+    /// vuelve al genoma dado.
+    Basin {
+        /// Genoma campeón (JSON).
+        #[arg(short, long)]
+        genome: String,
+        /// Número de arranques aleatorios.
+        #[arg(long, default_value_t = 200, value_parser = clap::value_parser!(u32).range(1..))]
+        samples: u32,
+        /// Desviación típica (en logaritmo natural) de la perturbación de cada arranque.
+        #[arg(long, default_value_t = 0.1, value_parser = parse_positive)]
+        radius: f64,
+        /// Iteraciones del escalador de colinas por arranque.
+        #[arg(long, default_value_t = 300, value_parser = clap::value_parser!(u32).range(1..))]
+        iterations: u32,
+        /// Distancia log-genética al campeón por debajo de la cual un arranque ha convergido. Los genes
+        /// que no afectan al fitness conservan su perturbación inicial y cuentan en la distancia.
+        #[arg(long, default_value_t = 0.5, value_parser = parse_positive)]
+        tolerance: f64,
+        /// Semilla base; cada arranque usa su propio generador derivado de ella. Aleatoria si se omite.
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+        #[arg(long)]
+        weights: Option<String>,
+    },
+    /// Índices de Sobol: contribución de cada gen (sola y con sus interacciones) a la varianza del
+    /// fitness en toda la caja de parámetros.
+    Sobol {
+        /// Filas de cada matriz de Saltelli; el coste es `samples · (genes + 2)` evaluaciones.
+        #[arg(long, default_value_t = 10_000, value_parser = clap::value_parser!(u32).range(1..))]
+        samples: u32,
+        /// Semilla del muestreo; aleatoria si se omite.
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+        #[arg(long)]
+        weights: Option<String>,
+    },
+    /// Desglose completo del fitness de un genoma: magnitudes intermedias, sub-puntuaciones,
+    /// contribuciones ponderadas, barrera que lo detuvo, nivel y robustez.
+    Inspect {
+        /// Genoma a inspeccionar (JSON).
+        genome: String,
+        /// Vuelca el `FitnessReport` como JSON en lugar del texto.
+        #[arg(long, conflicts_with = "latex")]
+        json: bool,
+        /// Emite la tabla `tabular` de LaTeX del genoma (la misma que `report-card --latex`).
+        #[arg(long)]
+        latex: bool,
+        /// Perturbaciones con las que se estima la robustez.
+        #[arg(long, default_value_t = 32, value_parser = parse_count)]
+        robustness_samples: usize,
+        /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+        #[arg(long)]
+        weights: Option<String>,
+    },
+    /// Boletín de notas: ficha en Markdown de un genoma con una nota (A–F) por dominio físico.
+    ReportCard {
+        /// Genoma a evaluar (JSON).
+        #[arg(short, long)]
+        genome: String,
+        /// Archivo Markdown de salida; por defecto, la salida estándar.
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+        #[arg(long)]
+        weights: Option<String>,
+        /// Emite una tabla `tabular` de LaTeX (requiere `siunitx`) en lugar del boletín en Markdown.
+        #[arg(long)]
+        latex: bool,
+    },
+    /// Ajuste fino: intervalo contiguo alrededor de cada constante en el que el universo conserva su
+    /// nivel de complejidad, de la constante más ajustada a la menos.
+    FineTuning {
+        /// Genoma a analizar (JSON).
+        #[arg(short, long)]
+        genome: String,
+        /// Precisión relativa de cada frontera (en logaritmo natural).
+        #[arg(long, default_value_t = 1e-4, value_parser = parse_positive)]
+        precision: f64,
+        /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+        #[arg(long)]
+        weights: Option<String>,
+    },
+    /// Rugosidad del paisaje: autocorrelación del fitness a lo largo de un paseo aleatorio en el
+    /// espacio log-genético y longitud de correlación (Weinberger).
+    Ruggedness {
+        /// Genoma de partida (JSON); por defecto, nuestro universo.
+        #[arg(short, long)]
+        genome: Option<String>,
+        /// Pasos del paseo aleatorio.
+        #[arg(long, default_value_t = 10_000, value_parser = clap::value_parser!(u32).range(2..))]
+        steps: u32,
+        /// Desviación típica (en logaritmo natural) del paso gaussiano de cada gen.
+        #[arg(long, default_value_t = 0.05, value_parser = parse_positive)]
+        step_size: f64,
+        /// Semilla del paseo; aleatoria si se omite.
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+        #[arg(long)]
+        weights: Option<String>,
+    },
+    /// Evalúa cada genoma de un CSV con el genoma completo, sin muestreo aleatorio, y escribe las
+    /// columnas de entrada más el fitness, el nivel y las sub-puntuaciones.
+    EvalGrid {
+        /// CSV de entrada con una columna por gen (el formato de `map` sin `--compat-csv`).
+        #[arg(short, long)]
+        input: String,
+        /// CSV de salida.
+        #[arg(short, long, default_value = "eval_grid.csv")]
+        output: String,
+        /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+        #[arg(long)]
+        weights: Option<String>,
+    },
+    /// Perfil de una constante: fitness y nivel a lo largo de su rango (en escala logarítmica) con
+    /// el resto del genoma fijo, y los valores exactos en los que cambia el nivel.
+    Profile {
+        /// Gen a recorrer (nombre del campo, p. ej. `alpha_s`).
+        #[arg(value_parser = parse_gene)]
+        gene: Gene,
+        /// Genoma que fija el resto de constantes (JSON); por defecto, nuestro universo.
+        #[arg(short, long)]
+        genome: Option<String>,
+        /// Puntos del perfil, espaciados logarítmicamente sobre el rango de muestreo del gen.
+        #[arg(long, default_value_t = 500, value_parser = clap::value_parser!(u32).range(2..))]
+        steps: u32,
+        /// Precisión relativa (en logaritmo natural) de cada transición de nivel.
+        #[arg(long, default_value_t = 1e-6, value_parser = parse_positive)]
+        precision: f64,
+        /// CSV de salida; por defecto, `profile_<gen>.csv`.
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+        #[arg(long)]
+        weights: Option<String>,
+    },
+    /// Barrido lineal de una constante: fitness y nivel en `steps` valores equiespaciados entre
+    /// `--min` y `--max`, con el resto del genoma fijo.
+    Sweep {
+        /// Genoma base (JSON).
+        genome: String,
+        /// Gen a recorrer (nombre del campo, p. ej. `alpha_s` o `mass_electron`).
+        #[arg(long, value_parser = parse_gene)]
+        param: Gene,
+        #[arg(long, value_parser = parse_positive)]
+        min: f64,
+        #[arg(long, value_parser = parse_positive)]
+        max: f64,
+        #[arg(long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(2..))]
+        steps: u32,
+        /// CSV de salida (`param_value,fitness,level`).
+        #[arg(short, long, default_value = "sweep_data.csv")]
+        output: String,
+        /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+        #[arg(long)]
+        weights: Option<String>,
+    },
+    /// Busca un genoma representativo de cada nivel de complejidad (0 a 4) y los guarda como
+    /// `level_<n>.json` en un directorio, para usarlos como ejemplos o fijaciones de regresión.
+    GenerateSuite {
+        /// Directorio de salida (se crea si no existe).
+        dir: String,
+        /// Universos aleatorios del muestreo inicial, que cubre los niveles frecuentes.
+        #[arg(long, default_value_t = 20_000, value_parser = clap::value_parser!(u64).range(1..))]
+        samples: u64,
+        /// Escaladas de colina desde el mejor genoma muestreado para alcanzar los niveles raros.
+        #[arg(long, default_value_t = 20, value_parser = clap::value_parser!(u64).range(1..))]
+        attempts: u64,
+        /// Pasos de cada escalada.
+        #[arg(long, default_value_t = 2_000, value_parser = clap::value_parser!(u32).range(1..))]
+        iterations: u32,
+        /// Semilla de la búsqueda; aleatoria si se omite.
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+        #[arg(long)]
+        weights: Option<String>,
+    },
+    /// Topología de masas: evalúa cada ordenación relativa de las tres generaciones de leptones
+    /// cargados, quarks tipo up y quarks tipo down, conservando los valores de masa del genoma.
+    Topology {
+        /// Genoma que aporta los valores de masa y el resto de constantes (JSON); por defecto,
+        /// nuestro universo.
+        #[arg(short, long)]
+        genome: Option<String>,
+        /// CSV de salida, una fila por ordenación.
+        #[arg(short, long, default_value = "topology.csv")]
+        output: String,
+        /// Nivel de complejidad mínimo para considerar viable una ordenación.
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(0..=4))]
+        min_level: u8,
+        /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+        #[arg(long)]
+        weights: Option<String>,
+    },
+    /// Barrido de tolerancias: multiplica todas las anchuras de `ToleranceConfig` por un factor y,
+    /// para cada factor, mide la fracción viable sobre los mismos universos aleatorios.
+    ToleranceSweep(ToleranceSweepArgs),
+    /// Comprueba que las fórmulas del motor escalan con las constantes según su análisis dimensional.
+    CheckDimensions,
+    /// Dimensión fractal (conteo de cajas) de la región viable proyectada sobre dos acoplamientos.
+    FractalDimension {
+        /// CSV del paisaje con el genoma completo (`map` sin `--compat-csv`).
+        #[arg(short, long, default_value = "landscape_data.csv")]
+        input: String,
+        /// Acoplamiento del eje X de la proyección.
+        #[arg(long, value_enum, default_value_t = PhaseAxis::AlphaS)]
+        x: PhaseAxis,
+        /// Acoplamiento del eje Y de la proyección.
+        #[arg(long, value_enum, default_value_t = PhaseAxis::Alpha)]
+        y: PhaseAxis,
+        /// Nivel de complejidad mínimo para pertenecer al conjunto viable.
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=4))]
+        min_level: u8,
+        /// La escala más fina usa 2^N cajas por eje; la más gruesa, 2 cajas.
+        #[arg(long, default_value_t = 7, value_parser = clap::value_parser!(u32).range(2..=16))]
+        max_exponent: u32,
+    },
+    /// Matriz de correlación de los log-genes entre los universos viables de un mapeo.
+    GeneCorrelation {
+        /// CSV del paisaje con el genoma completo (`map` sin `--compat-csv`).
+        #[arg(short, long, default_value = "landscape_data.csv")]
+        input: String,
+        /// CSV de salida con la matriz de correlación (una fila y una columna por gen).
+        #[arg(short, long, default_value = "gene_correlation.csv")]
+        output: String,
+        /// Coeficiente de correlación.
+        #[arg(long, value_enum, default_value_t = CorrelationMethod::Pearson)]
+        method: CorrelationMethod,
+        /// Nivel de complejidad mínimo para entrar en el cálculo.
+        #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=4))]
+        min_level: u8,
+    },
+}
+
+/// Coeficiente de la matriz de correlación entre genes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum CorrelationMethod {
+    /// Pearson sobre los logaritmos de los genes.
+    Pearson,
+    /// Spearman: Pearson sobre los rangos (insensible a cualquier transformación monótona).
+    Spearman,
+}
+
+#[derive(Args, Serialize)]
+struct MapArgs {
+    #[arg(short, long, default_value_t = 5_000_000, value_parser = clap::value_parser!(u64).range(1..))]
+    universes: u64,
+    /// Exporta un diagrama de fases (nivel modal y fracción viable por celda) a este CSV.
+    #[arg(long)]
+    phase_diagram: Option<String>,
+    /// Acoplamiento del eje X del diagrama de fases.
+    #[arg(long, value_enum, default_value_t = PhaseAxis::AlphaS)]
+    phase_x: PhaseAxis,
+    /// Acoplamiento del eje Y del diagrama de fases.
+    #[arg(long, value_enum, default_value_t = PhaseAxis::Alpha)]
+    phase_y: PhaseAxis,
+    /// Número de celdas por eje del diagrama de fases.
+    #[arg(long, default_value_t = 50, value_parser = parse_count)]
+    phase_bins: usize,
+    /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+    #[arg(long)]
+    weights: Option<String>,
+    /// Muestreo estratificado por hipercubo latino en lugar de muestreo uniforme independiente.
+    #[arg(long)]
+    stratified: bool,
+    /// Tamaño de la muestra uniforme de universos viables escrita en el CSV (muestreo por reservorio).
+    #[arg(long, default_value_t = 10_000, value_parser = parse_count)]
+    sample_size: usize,
+    /// Detiene la evaluación en este nivel de complejidad (omite las puntuaciones de niveles superiores).
+    #[arg(long, default_value_t = MAX_COMPLEXITY_LEVEL, value_parser = clap::value_parser!(u8).range(0..=4))]
+    max_level: u8,
+    /// Mide el tiempo de cada método de puntuación del motor de física e imprime un desglose al final.
+    #[arg(long)]
+    profile: bool,
+    /// Imprime la memoria asignada en cada hito del mapeo y el pico al final.
+    #[arg(long)]
+    profile_memory: bool,
+    /// Añade a cada muestra impresa sus constantes clave como múltiplos de las de nuestro universo.
+    #[arg(long)]
+    compare_to_real: bool,
+    /// Escribe el CSV con el formato antiguo (solo seis masas de quarks) en lugar del genoma completo.
+    #[arg(long)]
+    compat_csv: bool,
+    /// Formato de la muestra: CSV (`landscape_data.csv`) o un objeto JSON por línea
+    /// (`landscape_data.jsonl`) con el genoma completo, el fitness, el nivel y el tipo de universo.
+    #[arg(long, value_enum, default_value_t = LandscapeFormat::Csv)]
+    format: LandscapeFormat,
+    /// Añade a cada universo de la muestra las sub-puntuaciones sin ponderar de cada dominio
+    /// (`chemistry_score`, `nuclear_score`, `stellar_score`, `heavy_elements_score`, `reproduction_score`).
+    #[arg(long)]
+    detailed: bool,
+    /// Solo los universos que alcanzan al menos este nivel de complejidad entran en la muestra del CSV.
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u8).range(0..=4))]
+    require_level: u8,
+    /// Semilla base del mapeo; por defecto se elige una al azar y se imprime. Con muestreo uniforme,
+    /// `replay --seed <semilla> --index <i>` reconstruye el universo `i`.
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Guarda el recuento de resultados por categoría (JSON) para `bootstrap-ci`.
+    #[arg(long)]
+    outcomes: Option<String>,
+    /// Mapea en paralelo con este número de hilos; sin valor (o con 0), con todos los núcleos.
+    /// Implica `--deterministic` (salvo el orden de las filas con `--no-sampling`, que es el de
+    /// llegada; la columna `index` permite reordenarlas).
+    #[arg(long, num_args = 0..=1, default_missing_value = "0", conflicts_with_all = ["stratified", "profile"])]
+    threads: Option<usize>,
+    /// Muestra independiente del orden de evaluación (prioridad derivada del índice de cada universo)
+    /// y CSV ordenado por índice: para una semilla dada, la salida es idéntica byte a byte con
+    /// cualquier número de hilos.
+    #[arg(long, conflicts_with = "stratified")]
+    deterministic: bool,
+    /// Escribe todos los universos elegibles en lugar de una muestra. La escritura corre en un hilo
+    /// propio alimentado por un canal acotado: si el disco no da abasto, la evaluación se frena en
+    /// vez de acumular filas en memoria. El CSV crece sin límite (~150 bytes por universo) y el
+    /// rendimiento queda limitado por el disco; el muestreo por defecto usa memoria y disco fijos.
+    #[arg(long, conflicts_with = "deterministic")]
+    no_sampling: bool,
+    /// Escribe un manifiesto del experimento (JSON) con todo lo necesario para repetirlo con
+    /// `reproduce`: versión, commit, argumentos con la semilla efectiva, configuración y el
+    /// SHA-256 de cada salida.
+    #[arg(long)]
+    write_manifest: Option<String>,
+}
+
+#[derive(Args, Serialize)]
+struct ToleranceSweepArgs {
+    /// Factor mínimo (más estricto) del barrido.
+    #[arg(long, default_value_t = 0.25, value_parser = parse_positive)]
+    min_factor: f64,
+    /// Factor máximo (más permisivo) del barrido.
+    #[arg(long, default_value_t = 4.0, value_parser = parse_positive)]
+    max_factor: f64,
+    /// Factores del barrido, espaciados logarítmicamente.
+    #[arg(long, default_value_t = 9, value_parser = clap::value_parser!(u32).range(2..))]
+    steps: u32,
+    /// Universos evaluados con cada factor.
+    #[arg(short, long, default_value_t = 20_000, value_parser = clap::value_parser!(u64).range(1..))]
+    universes: u64,
+    /// Semilla de los universos (los mismos para todos los factores); aleatoria si se omite.
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Nivel de complejidad mínimo para contar un universo como viable.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(0..=4))]
+    min_level: u8,
+    /// CSV de salida con la curva.
+    #[arg(short, long, default_value = "tolerance_sweep.csv")]
+    output: String,
+    /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+    #[arg(long)]
+    weights: Option<String>,
+}
+
+#[derive(Args, Serialize)]
+struct EvolveArgs {
+    /// Genoma de Adán (JSON). Con `--seed-population` es opcional: si falta, el primer genoma de
+    /// la población hace de Adán. Con `--resume`, el de Adán es el del punto de control.
+    #[arg(short, long, required_unless_present_any = ["seed_population", "resume"])]
+    seed: Option<String>,
+    #[arg(short, long, default_value_t = 500, value_parser = clap::value_parser!(u64).range(1..))]
+    generations: u64,
+    /// Registra además el mejor fitness entre los individuos nuevos (excluyendo la élite).
+    #[arg(long)]
+    track_offspring: bool,
+    /// Función objetivo que guía la selección (`all` equivale a `complexity`).
+    #[arg(long, value_enum, default_value_t = Objective::Complexity)]
+    objective: Objective,
+    /// Genoma objetivo (JSON) para `--objective target-distance`.
+    #[arg(long, required_if_eq("objective", "target-distance"))]
+    target: Option<String>,
+    /// Número de rondas; cada una parte del campeón de la anterior tras una ráfaga de diversidad.
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    rounds: u32,
+    /// Archivo JSON con los pesos de la función de fitness (`FitnessWeights`).
+    #[arg(long)]
+    weights: Option<String>,
+    /// Usa optimización por enjambre de partículas (PSO) en espacio log-genético en lugar del AG.
+    #[arg(long, conflicts_with = "rounds")]
+    pso: bool,
+    /// Inercia de la velocidad de cada partícula (PSO).
+    #[arg(long, default_value_t = 0.729)]
+    inertia: f64,
+    /// Coeficiente cognitivo: atracción hacia el mejor personal (PSO).
+    #[arg(long, default_value_t = 1.494)]
+    cognitive: f64,
+    /// Coeficiente social: atracción hacia el mejor global (PSO).
+    #[arg(long, default_value_t = 1.494)]
+    social: f64,
+    /// Añade al CSV la columna `gen_millis`: milisegundos de evaluación y reproducción de cada generación.
+    #[arg(long)]
+    record_timing: bool,
+    /// Escribe el genoma campeón en `best_so_far.json` cada vez que mejora el mejor fitness.
+    #[arg(long)]
+    emit_seed_on_improvement: bool,
+    /// Sustituye el paso multiplicativo (±5%) de la mutación por un paso gaussiano con esta
+    /// desviación típica, en unidades de logaritmo natural.
+    #[arg(long, value_parser = parse_positive)]
+    gaussian_sigma: Option<f64>,
+    /// Probabilidad de que un hijo intercambie además dos masas de la misma familia
+    /// (quarks tipo up, quarks tipo down o leptones cargados).
+    #[arg(long, default_value_t = 0.0, value_parser = parse_probability)]
+    swap_rate: f64,
+    /// Probabilidad de que un hijo nazca de la recombinación de dos padres (elegidos por torneo)
+    /// antes de mutar, en lugar de mutar a un único padre.
+    #[arg(long, default_value_t = 0.0, value_parser = parse_probability, conflicts_with = "pso")]
+    crossover_rate: f64,
+    /// La recombinación interpola cada gen entre los padres (en escala logarítmica) en lugar de
+    /// heredarlo entero de uno de ellos.
+    #[arg(long, requires = "crossover_rate")]
+    crossover_blend: bool,
+    /// Perturbaciones evaluadas por genoma con `--objective robustness`.
+    #[arg(long, default_value_t = 32, value_parser = parse_count)]
+    robustness_samples: usize,
+    /// Muestreo de los contendientes de cada torneo de selección.
+    #[arg(long, value_enum, default_value_t = TournamentReplacement::With)]
+    tournament_replacement: TournamentReplacement,
+    /// CSV en formato largo (`generation,gene_name,value`) con el genoma del campeón por generación.
+    #[arg(long)]
+    trajectory: Option<String>,
+    /// Registra la trayectoria solo cada N generaciones.
+    #[arg(long, default_value_t = 1, requires = "trajectory", value_parser = clap::value_parser!(u64).range(1..))]
+    trajectory_every: u64,
+    /// Ejecuta N evoluciones independientes (en paralelo, cada una con su propio generador) y
+    /// conserva el mejor campeón. Escribe un resumen por evolución en `restarts.csv` y el mejor
+    /// genoma en `best_restart.json` en lugar de `evolution_data.csv`.
+    #[arg(long, value_parser = parse_count, conflicts_with_all = ["pso", "trajectory", "emit_seed_on_improvement", "track_offspring", "record_timing", "newick"])]
+    restarts: Option<usize>,
+    /// Registra el linaje completo y escribe el árbol filogenético de la población final (Newick),
+    /// con ramas de longitud igual a la distancia log-genética entre padre e hijo.
+    #[arg(long, conflicts_with = "pso")]
+    newick: Option<String>,
+    /// Array JSON de genomas (`CosmicLaw`) que forma la población inicial en lugar de los mutantes
+    /// de Adán. Si sobran genomas se conservan los más aptos; si faltan, se rellena con mutantes.
+    #[arg(long, conflicts_with = "pso")]
+    seed_population: Option<String>,
+    /// Generaciones de la ventana deslizante con la que se diagnostica si la evolución mejora,
+    /// oscila o se ha estancado.
+    #[arg(long, default_value_t = 50, value_parser = clap::value_parser!(u32).range(3..))]
+    convergence_window: u32,
+    /// Mutación adaptativa: tras N generaciones sin que el campeón mejore, duplica la tasa de
+    /// mutación y la probabilidad de hipermutación hasta que vuelva a mejorar, y después las hace
+    /// decaer hacia las de base. Añade al CSV la columna `mutation_rate`. Sin ella, las tasas son fijas.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..), conflicts_with = "pso")]
+    stagnation_window: Option<u64>,
+    /// Mejora mínima del fitness del campeón que interrumpe el estancamiento.
+    #[arg(long, default_value_t = CONVERGENCE_TOLERANCE, requires = "stagnation_window", value_parser = parse_positive)]
+    stagnation_epsilon: f64,
+    /// Semilla del generador aleatorio; por defecto se elige una al azar y se imprime. Con la misma
+    /// semilla (y el mismo número de reinicios) la evolución se repite exactamente.
+    #[arg(long)]
+    rng_seed: Option<u64>,
+    /// Cada cuántas generaciones se guarda un punto de control (`checkpoint_gen_<n>.json`, con `n`
+    /// la generación por la que continuaría) para retomar la evolución con `--resume`.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..), conflicts_with_all = ["pso", "restarts"])]
+    checkpoint_interval: Option<u64>,
+    /// Retoma la evolución desde un punto de control: población, élite, generador y contadores. Las
+    /// filas de `evolution_data.csv` (y de `--trajectory`) posteriores al punto de control se
+    /// descartan y la evolución sigue añadiendo filas a partir de él.
+    #[arg(long, conflicts_with_all = ["seed", "seed_population", "pso", "restarts", "newick", "rng_seed"])]
+    resume: Option<String>,
+    /// Archivo JSON donde se guarda el genoma campeón al terminar, con su fitness y su nivel.
+    /// Con `--restarts`, el mejor genoma se guarda en `best_restart.json`.
+    #[arg(short, long, default_value = "champion.json", conflicts_with = "restarts")]
+    output: String,
+    /// Escribe un manifiesto del experimento (JSON) para repetirlo con `reproduce`, como en `map`.
+    /// No admite `--resume` (el estado sale del punto de control) ni `--record-timing` (los
+    /// tiempos cambian en cada ejecución).
+    #[arg(long, conflicts_with_all = ["resume", "record_timing"])]
+    write_manifest: Option<String>,
+}
+
+/// Probabilidad en [0, 1].
+fn parse_probability(s: &str) -> Result<f64, String> {
+    let value: f64 = s.parse().map_err(|e| format!("{}", e))?;
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("debe ser una probabilidad en [0, 1] (es {})", value))
+    }
+}
+
+/// Gen por el nombre de su campo en `CosmicLaw`.
+fn parse_gene(s: &str) -> Result<Gene, String> {
+    GENES.iter().position(|spec| spec.name == s)
+        .map(|index| Gene::ALL[index])
+        .ok_or_else(|| {
+            let names: Vec<&str> = GENES.iter().map(|spec| spec.name).collect();
+            format!("gen desconocido (valores posibles: {})", names.join(", "))
+        })
+}
+
+/// Valor estrictamente entre 0 y 1.
+fn parse_open_unit_interval(s: &str) -> Result<f64, String> {
+    let value: f64 = s.parse().map_err(|e| format!("{}", e))?;
+    if value > 0.0 && value < 1.0 {
+        Ok(value)
+    } else {
+        Err(format!("debe estar en (0, 1) (es {})", value))
+    }
+}
+
+/// Número real finito y mayor que cero.
+fn parse_positive(s: &str) -> Result<f64, String> {
+    let value: f64 = s.parse().map_err(|e| format!("{}", e))?;
+    if value.is_finite() && value > 0.0 {
+        Ok(value)
+    } else {
+        Err(format!("debe ser un número finito mayor que 0 (es {})", value))
+    }
+}
+
+/// Recuento `>= 1`.
+fn parse_count(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>().map_err(|e| format!("{}", e))? {
+        0 => Err("debe ser al menos 1".to_string()),
+        value => Ok(value),
+    }
+}
+
+/// Criterio de fitness usado por el modo evolutivo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum Objective {
+    /// Escalera de complejidad física (`calculate_fitness`): la suma ponderada de todos los dominios.
+    #[value(alias = "all")]
+    Complexity,
+    /// Distancia negativa en espacio log-genético a un genoma objetivo conocido (validación del AG).
+    TargetDistance,
+    /// Residuo relativo total (negativo) de los observables derivados frente a nuestro universo.
+    MinimizeResidual,
+    /// Fitness medio bajo perturbaciones del ~1% de cada gen (`PhysicsEngine::robustness_score`).
+    Robustness,
+    /// Solo la sub-puntuación química (`EngineSummary::chemistry`), sin las barreras de la escalera.
+    Chemistry,
+    /// Solo la sub-puntuación nuclear.
+    Nuclear,
+    /// Solo la sub-puntuación estelar (incluida la formación de estructuras).
+    Stellar,
+    /// Solo la sub-puntuación de elementos pesados.
+    HeavyElements,
+    /// Solo la sub-puntuación reproductiva (agujeros negros).
+    Reproductive,
+}
+
+impl Objective {
+    /// Posición en `EngineSummary::domains` del dominio que optimiza un objetivo de una sola
+    /// sub-puntuación; `None` para los demás objetivos.
+    fn domain(self) -> Option<usize> {
+        match self {
+            Objective::Chemistry => Some(0),
+            Objective::Nuclear => Some(1),
+            Objective::Stellar => Some(2),
+            Objective::HeavyElements => Some(3),
+            Objective::Reproductive => Some(4),
+            Objective::Complexity | Objective::TargetDistance | Objective::MinimizeResidual | Objective::Robustness => None,
+        }
+    }
+}
+
+/// Muestreo de los contendientes de un torneo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum TournamentReplacement {
+    /// Con reemplazo: un mismo individuo puede ocupar varios puestos del torneo.
+    With,
+    /// Sin reemplazo: contendientes distintos (el tamaño se acota al de la población).
+    Without,
+}
+
+// --- FUNCIÓN PRINCIPAL (PUNTO DE ENTRADA) ---
+fn main() {
+    let cli = Cli::parse();
+    JSON_LOGS.store(cli.json_logs, Ordering::Relaxed);
+    KAHAN_SUMMATION.store(cli.kahan_sum, Ordering::Relaxed);
+    PROGRESS_BARS.store(!cli.quiet && !cli.json_logs && std::io::stdout().is_terminal(), Ordering::Relaxed);
+    if let Err(e) = load_global_config(&cli) {
+        eprintln!("Error en la ejecución: {}", e);
+        std::process::exit(1);
+    }
+
+    let result = match &cli.command {
+        Commands::Map(args) => {
+            progress!("--- INICIANDO MODO MAPEO ---");
+            run_mapping_mode(args)
+        }
+        Commands::Evolve(args) => {
+            progress!("--- INICIANDO MODO EVOLUTIVO ---");
+            run_evolutionary_mode(args)
+        }
+        Commands::HabitableVolume { genome, steps, min_level, weights } => {
+            progress!("--- INICIANDO ANÁLISIS DE VOLUMEN HABITABLE ---");
+            run_habitable_volume_mode(genome, *steps, *min_level, weights.as_deref())
+        }
+        Commands::Calibrate { genome, iterations, output } => {
+            progress!("--- INICIANDO CALIBRACIÓN DE PESOS ---");
+            run_calibration_mode(genome.as_deref(), *iterations, output)
+        }
+        Commands::DistanceMatrix { input, output } => {
+            progress!("--- CALCULANDO MATRIZ DE DISTANCIAS ---");
+            run_distance_matrix_mode(input, output)
+        }
+        Commands::Basin { genome, samples, radius, iterations, tolerance, seed, weights } => {
+            progress!("--- ESTIMANDO CUENCA DE ATRACCIÓN ---");
+            run_basin_mode(genome, *samples, *radius, *iterations, *tolerance, *seed, weights.as_deref())
+        }
+        Commands::Sobol { samples, seed, weights } => {
+            progress!("--- ANÁLISIS DE SENSIBILIDAD GLOBAL (SOBOL) ---");
+            run_sobol_mode(*samples, *seed, weights.as_deref())
+        }
+        Commands::Inspect { genome, json, latex, robustness_samples, weights } => {
+            run_inspect_mode(genome, *json, *latex, *robustness_samples, weights.as_deref())
+        }
+        Commands::ReportCard { genome, output, weights, latex } => {
+            run_report_card_mode(genome, output.as_deref(), weights.as_deref(), *latex)
+        }
+        Commands::FineTuning { genome, precision, weights } => {
+            progress!("--- ANÁLISIS DE AJUSTE FINO ---");
+            run_fine_tuning_mode(genome, *precision, weights.as_deref())
+        }
+        Commands::Ruggedness { genome, steps, step_size, seed, weights } => {
+            progress!("--- RUGOSIDAD DEL PAISAJE DE FITNESS ---");
+            run_ruggedness_mode(genome.as_deref(), *steps, *step_size, *seed, weights.as_deref())
+        }
+        Commands::EvalGrid { input, output, weights } => {
+            progress!("--- EVALUACIÓN DE UNA REJILLA DE GENOMAS ---");
+            run_eval_grid_mode(input, output, weights.as_deref())
+        }
+        Commands::Profile { gene, genome, steps, precision, output, weights } => {
+            progress!("--- PERFIL DE UNA CONSTANTE ---");
+            let output = output.clone().unwrap_or_else(|| format!("profile_{}.csv", gene.spec().name));
+            run_profile_mode(*gene, genome.as_deref(), *steps, *precision, &output, weights.as_deref())
+        }
+        Commands::Sweep { genome, param, min, max, steps, output, weights } => {
+            progress!("--- BARRIDO DE UNA CONSTANTE ---");
+            run_sweep_mode(genome, *param, *min, *max, *steps, output, weights.as_deref())
+        }
+        Commands::GenerateSuite { dir, samples, attempts, iterations, seed, weights } => {
+            progress!("--- GENERANDO COLECCIÓN DE GENOMAS POR NIVEL ---");
+            run_generate_suite_mode(dir, *samples, *attempts, *iterations, *seed, weights.as_deref())
+        }
+        Commands::Topology { genome, output, min_level, weights } => {
+            progress!("--- TOPOLOGÍA DE LAS JERARQUÍAS DE MASAS ---");
+            run_topology_mode(genome.as_deref(), output, *min_level, weights.as_deref())
+        }
+        Commands::ToleranceSweep(args) => {
+            progress!("--- BARRIDO DE TOLERANCIAS ---");
+            run_tolerance_sweep_mode(args)
+        }
+        Commands::CheckDimensions => {
+            progress!("--- COMPROBACIÓN DIMENSIONAL ---");
+            run_dimension_check()
+        }
+        Commands::Replay { seed, index, weights } => {
+            run_replay_mode(*seed, *index, weights.as_deref())
+        }
+        Commands::GeneCorrelation { input, output, method, min_level } => {
+            progress!("--- CALCULANDO CORRELACIONES ENTRE GENES ---");
+            run_gene_correlation_mode(input, output, *method, *min_level)
+        }
+        Commands::FractalDimension { input, x, y, min_level, max_exponent } => {
+            progress!("--- ESTIMANDO DIMENSIÓN FRACTAL ---");
+            run_fractal_dimension_mode(input, *x, *y, *min_level, *max_exponent)
+        }
+        Commands::ExpectedViable { outcomes, input, prior, min_level, universes } => {
+            progress!("--- FRACCIÓN VIABLE BAJO UNA MEDIDA A PRIORI ---");
+            run_expected_viable_mode(outcomes, input, prior, *min_level, *universes)
+        }
+        Commands::Reproduce { manifest } => {
+            progress!("--- REPRODUCCIÓN DE UN EXPERIMENTO ---");
+            run_reproduce_mode(manifest)
+        }
+        Commands::BootstrapCi { input, resamples, confidence } => {
+            progress!("--- INTERVALOS DE CONFIANZA BOOTSTRAP ---");
+            run_bootstrap_mode(input, *resamples, *confidence)
+        }
+    };
+
+    if let Some(cache) = FITNESS_CACHE.get() {
+        cache.report();
+    }
+    if let Err(e) = result {
+        eprintln!("Error en la ejecución: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Carga la configuración compartida por todos los modos (`--tolerances`, `--stellar-config`,
+/// `--gene-weights`, `--ranges`).
+fn load_global_config(cli: &Cli) -> Result<(), Box<dyn Error>> {
+    if let Some(path) = &cli.tolerances {
+        let _ = TOLERANCES.set(ToleranceConfig::load(path)?);
+    }
+    if let Some(path) = &cli.stellar_config {
+        let _ = STELLAR_PARAMS.set(StellarParams::load(path)?);
+    }
+    if let Some(path) = &cli.gene_weights {
+        let _ = GENE_WEIGHTS.set(GeneWeights::load(path)?);
+    }
+    if let Some(path) = &cli.ranges {
+        let _ = RANGES.set(RangeConfig::load(path)?);
+    }
+    if let Some(capacity) = cli.cache_size {
+        let _ = FITNESS_CACHE.set(FitnessCache::new(capacity));
+    }
+    if let Some(token) = &cli.non_finite_token {
+        let _ = NON_FINITE_TOKEN.set(token.clone());
+    }
+    Ok(())
+}
+
+// --- FORMATO DE LOS CSV ---
+/// Sustituto de los valores no finitos en los CSV (`--non-finite-token`).
+static NON_FINITE_TOKEN: OnceLock<String> = OnceLock::new();
+
+/// Celda de CSV para un `f64`: notación científica de ida y vuelta exacta (`{:e}`), o el sustituto
+/// de `--non-finite-token` (vacío por defecto) si el valor es NaN o infinito, que `NaN`/`inf` no
+/// los leen todas las herramientas.
+fn csv_float(value: f64) -> String {
+    if value.is_finite() {
+        format!("{:e}", value)
+    } else {
+        NON_FINITE_TOKEN.get().cloned().unwrap_or_default()
+    }
+}
+
+// --- COMPROBACIÓN DIMENSIONAL ---
+/// Relación de escala analítica: al multiplicar `scaled` por `SCALING_FACTOR`, el observable
+/// debe escalar como `SCALING_FACTOR^exponent`. Un exponente mal escrito en una fórmula produce
+/// números de apariencia plausible, pero rompe estas relaciones.
+struct ScalingRelation {
+    observable: &'static str,
+    scaled: ScaledQuantity,
+    exponent: f64,
+    measure: fn(&PhysicsEngine) -> f64,
+}
+
+/// Constante física o gen que se reescala en una relación de escala.
+#[derive(Debug, Clone, Copy)]
+enum ScaledQuantity {
+    C,
+    HBar,
+    Epsilon0,
+    KB,
+    Gene(Gene),
+}
+
+impl ScaledQuantity {
+    fn name(self) -> &'static str {
+        match self {
+            ScaledQuantity::C => "c",
+            ScaledQuantity::HBar => "h_bar",
+            ScaledQuantity::Epsilon0 => "epsilon_0",
+            ScaledQuantity::KB => "k_b",
+            ScaledQuantity::Gene(gene) => gene.spec().name,
+        }
+    }
+
+    fn apply(self, engine: &PhysicsEngine, factor: f64) -> PhysicsEngine {
+        let k = engine.constants;
+        let with_constants = |constants| PhysicsEngine::new(engine.laws.clone(), constants);
+        match self {
+            ScaledQuantity::C => with_constants(PhysicalConstants { c: k.c * factor, ..k }),
+            ScaledQuantity::HBar => with_constants(PhysicalConstants { h_bar: k.h_bar * factor, ..k }),
+            ScaledQuantity::Epsilon0 => with_constants(PhysicalConstants { epsilon_0: k.epsilon_0 * factor, ..k }),
+            ScaledQuantity::KB => with_constants(PhysicalConstants { k_b: k.k_b * factor, ..k }),
+            ScaledQuantity::Gene(gene) => engine.with_override(gene, engine.laws.gene(gene) * factor),
+        }
+    }
+}
+
+const SCALING_FACTOR: f64 = 2.0;
+/// Desviación admitida entre el exponente medido y el analítico.
+const SCALING_TOLERANCE: f64 = 1e-9;
+
+impl ScalingRelation {
+    /// Exponente efectivo del observable al reescalar `scaled` en el universo `base`.
+    fn measured_exponent(&self, base: &PhysicsEngine) -> f64 {
+        let scaled = self.scaled.apply(base, SCALING_FACTOR);
+        ((self.measure)(&scaled) / (self.measure)(base)).ln() / SCALING_FACTOR.ln()
+    }
+}
+
+fn scaling_relations() -> Vec<ScalingRelation> {
+    let chandrasekhar: fn(&PhysicsEngine) -> f64 = |e| e.chandrasekhar_mass();
+    let bohr: fn(&PhysicsEngine) -> f64 = |e| e.bohr_radius();
+    let alpha: fn(&PhysicsEngine) -> f64 = |e| e.alpha;
+    let deuteron: fn(&PhysicsEngine) -> f64 = |e| e.deuteron_binding_energy();
+    let gamow: fn(&PhysicsEngine) -> f64 = |e| e.pp_gamow_exponent(e.stellar.core_temperature).unwrap_or(f64::NAN);
+
+    let relation = |observable, scaled, exponent, measure| ScalingRelation { observable, scaled, exponent, measure };
+    vec![
+        // M_Ch = (ħc/G)^(3/2) / (μ_e·m_p)². La masa del protón es casi toda `NUCLEON_QCD_MASS`, fija,
+        // así que no se reescala con las masas de los quarks
+        relation("chandrasekhar_mass", ScaledQuantity::C, 1.5, chandrasekhar),
+        relation("chandrasekhar_mass", ScaledQuantity::HBar, 1.5, chandrasekhar),
+        relation("chandrasekhar_mass", ScaledQuantity::Gene(Gene::G), -1.5, chandrasekhar),
+        // a_0 = 4π·ε_0·ħ² / (m_e·e²)
+        relation("bohr_radius", ScaledQuantity::HBar, 2.0, bohr),
+        relation("bohr_radius", ScaledQuantity::Epsilon0, 1.0, bohr),
+        relation("bohr_radius", ScaledQuantity::Gene(Gene::MassElectron), -1.0, bohr),
+        relation("bohr_radius", ScaledQuantity::Gene(Gene::E), -2.0, bohr),
+        // α = e² / (4π·ε_0·ħ·c)
+        relation("alpha", ScaledQuantity::Gene(Gene::E), 2.0, alpha),
+        relation("alpha", ScaledQuantity::C, -1.0, alpha),
+        relation("alpha", ScaledQuantity::HBar, -1.0, alpha),
+        // B_d = κ·½·α_s²·μ·c²
+        relation("deuteron_binding", ScaledQuantity::Gene(Gene::AlphaS), 2.0, deuteron),
+        relation("deuteron_binding", ScaledQuantity::C, 2.0, deuteron),
+        // τ = 3·(E_G / 4kT)^(1/3), con E_G ∝ m·c²·α²
+        relation("pp_gamow_exponent", ScaledQuantity::KB, -1.0 / 3.0, gamow),
+        relation("pp_gamow_exponent", ScaledQuantity::Epsilon0, -2.0 / 3.0, gamow),
+        relation("pp_gamow_exponent", ScaledQuantity::HBar, -2.0 / 3.0, gamow),
+    ]
+}
+
+/// Combinación de un observable con las masas de los nucleones que no debe cambiar al variar estas.
+/// Las masas ya no escalan como potencias de ningún gen (casi toda la masa es `NUCLEON_QCD_MASS`),
+/// así que su dependencia se comprueba dividiéndola: un exponente mal escrito deja un residuo.
+struct MassInvariant {
+    observable: &'static str,
+    invariant: &'static str,
+    measure: fn(&PhysicsEngine) -> f64,
+}
+
+/// Genes que mueven las masas del protón y del neutrón: los quarks de valencia y `e` (autoenergía
+/// electromagnética del protón).
+const NUCLEON_MASS_GENES: [Gene; 3] = [Gene::MassUpQuark, Gene::MassDownQuark, Gene::E];
+/// Factor por el que se multiplica cada gen de `NUCLEON_MASS_GENES`: con 2× la masa del protón
+/// apenas cambia, con 100× cambia en decenas de por ciento.
+const MASS_SCALING_FACTOR: f64 = 100.0;
+
+impl MassInvariant {
+    /// Mayor desviación relativa del invariante al reescalar cada gen de `NUCLEON_MASS_GENES`.
+    fn max_deviation(&self, base: &PhysicsEngine) -> f64 {
+        let reference = (self.measure)(base);
+        NUCLEON_MASS_GENES.iter()
+            .map(|&gene| {
+                let scaled = base.with_override(gene, base.laws.gene(gene) * MASS_SCALING_FACTOR);
+                ((self.measure)(&scaled) / reference - 1.0).abs()
+            })
+            .fold(0.0, f64::max)
+    }
+}
+
+fn mass_invariants() -> Vec<MassInvariant> {
+    let invariant = |observable, invariant, measure| MassInvariant { observable, invariant, measure };
+    vec![
+        // M_Ch ∝ m_p⁻²
+        invariant("chandrasekhar_mass", "M_Ch·m_p²", |e| e.chandrasekhar_mass() * e.laws.proton_mass().powi(2)),
+        // B_d ∝ μ = m_p·m_n / (m_p + m_n)
+        invariant("deuteron_binding", "B_d/μ", |e| {
+            let (m_p, m_n) = (e.laws.proton_mass(), e.laws.neutron_mass());
+            e.deuteron_binding_energy() / (m_p * m_n / (m_p + m_n))
+        }),
+        // τ ∝ (m_p·α²)^(1/3); `e` también cambia α
+        invariant("pp_gamow_exponent", "τ³/(m_p·α²)", |e| {
+            e.pp_gamow_exponent(e.stellar.core_temperature).unwrap_or(f64::NAN).powi(3) / (e.laws.proton_mass() * e.alpha.powi(2))
+        }),
+    ]
+}
+
+/// Mide el exponente de cada relación de escala (y cada invariante de masa) sobre nuestro universo
+/// y falla si alguno se desvía.
+fn run_dimension_check() -> Result<(), Box<dyn Error>> {
+    let base = PhysicsEngine::from_real_universe();
+    let mut failures = 0;
+    for relation in scaling_relations() {
+        let measured = relation.measured_exponent(&base);
+        let ok = (measured - relation.exponent).abs() < SCALING_TOLERANCE;
+        if !ok { failures += 1; }
+        progress!(
+            "{:<4} {:<20} ∝ {:<14}^{:<8.4} (medido {:.6})",
+            if ok { "OK" } else { "FALLO" }, relation.observable, relation.scaled.name(), relation.exponent, measured,
+        );
+    }
+    for invariant in mass_invariants() {
+        let deviation = invariant.max_deviation(&base);
+        let ok = deviation < SCALING_TOLERANCE;
+        if !ok { failures += 1; }
+        progress!(
+            "{:<4} {:<20} {:<14} invariante con m_u, m_d y e (desviación {:.2e})",
+            if ok { "OK" } else { "FALLO" }, invariant.observable, invariant.invariant, deviation,
+        );
+    }
+    if failures > 0 {
+        return Err(format!("{} relaciones de escala no se cumplen", failures).into());
+    }
+    progress!("Todas las relaciones de escala se cumplen.");
+    Ok(())
+}
+
+// --- LÓGICA DEL MODO VOLUMEN HABITABLE ---
+/// Valores espaciados logarítmicamente que recorren el rango de muestreo de un gen.
+fn log_sweep(spec: &GeneSpec, steps: u32) -> impl Iterator<Item = f64> {
+    let (lo, hi) = (spec.min.ln(), spec.max.ln());
+    let steps = steps.max(2);
+    (0..steps).map(move |i| (lo + (hi - lo) * i as f64 / (steps - 1) as f64).exp())
+}
+
+/// Fracción habitable de cada gen con el resto fijado en `reference`, de más restringido (menor
+/// fracción habitable) a menos.
+fn habitable_fractions(reference: &CosmicLaw, weights: &FitnessWeights, steps: u32, min_level: u8) -> Vec<(&'static str, f64)> {
+    let ranges = RangeConfig::active();
+    let mut fractions: Vec<(&str, f64)> = Gene::ALL.iter()
+        .map(|&gene| {
+            let spec = ranges.spec(gene);
+            let habitable = log_sweep(spec, steps)
+                .filter(|&value| calculate_fitness(&reference.with_gene(gene, value), weights).level >= min_level)
+                .count();
+            (spec.name, habitable as f64 / steps.max(2) as f64)
+        })
+        .collect();
+    fractions.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+    fractions
+}
+
+fn run_habitable_volume_mode(genome_file: &str, steps: u32, min_level: u8, weights_file: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let reference = CosmicLaw::load(genome_file)?;
+    let weights = match weights_file {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    let FitnessReport { total: reference_fitness, level: reference_level, .. } = calculate_fitness(&reference, &weights);
+    progress!("Referencia: fitness {:.6}, nivel {} ({})", *reference_fitness, reference_level, classify_universe(&reference, reference_fitness, reference_level));
+
+    let fractions = habitable_fractions(&reference, &weights, steps, min_level);
+    progress!("Fracción habitable (nivel >= {}) del rango logarítmico de cada gen:", min_level);
+    for (rank, (name, fraction)) in fractions.iter().enumerate() {
+        progress!("{:>2}. {:<20} {:>7.2}%", rank + 1, name, fraction * 100.0);
+    }
+    Ok(())
+}
+
+// --- ESCALADOR DE COLINAS ---
+/// Búsqueda local estocástica: propone vecinos y acepta los que mejoran la puntuación. Con
+/// `accept_ties` también acepta los que la igualan, lo que permite desplazarse por mesetas (pero
+/// también derivar por dimensiones neutras). Devuelve el mejor punto y su puntuación.
+fn hill_climb<T: Clone>(
+    initial: T,
+    iterations: u32,
+    accept_ties: bool,
+    rng: &mut impl Rng,
+    mut neighbor: impl FnMut(&T, &mut dyn RngCore) -> T,
+    score: impl Fn(&T) -> f64,
+) -> (T, f64) {
+    let mut current_score = score(&initial);
+    let mut current = initial;
+    for _ in 0..iterations {
+        let candidate = neighbor(&current, rng);
+        let candidate_score = score(&candidate);
+        if candidate_score > current_score || (accept_ties && candidate_score == current_score) {
+            current = candidate;
+            current_score = candidate_score;
+        }
+    }
+    (current, current_score)
+}
+
+// --- LÓGICA DEL MODO CUENCA DE ATRACCIÓN ---
+fn run_basin_mode(
+    genome_file: &str,
+    samples: u32,
+    radius: f64,
+    iterations: u32,
+    tolerance: f64,
+    seed: Option<u64>,
+    weights_file: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    // Paso local del escalador: cada gen muta con esta probabilidad un paso gaussiano de esta anchura
+    const CLIMB_RATE: f64 = 0.3;
+    const CLIMB_STEP: f64 = 0.05;
+
+    let champion = CosmicLaw::load(genome_file)?;
+    let weights = match weights_file {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    let seed = seed.unwrap_or_else(|| thread_rng().gen());
+    progress!("Semilla: {}", seed);
+    let pool = RngPool::new(seed);
+    let champion_logs = LogGenome::from_laws(&champion);
+    let FitnessReport { total: champion_fitness, level: champion_level, .. } = calculate_fitness(&champion, &weights);
+    progress!("Campeón: fitness {:.6}, nivel {} ({})", *champion_fitness, champion_level, classify_universe(&champion, champion_fitness, champion_level));
+
+    let score = |genome: &LogGenome| *calculate_fitness(&genome.to_laws(), &weights).total;
+    let neighbor = |genome: &LogGenome, mut rng: &mut dyn RngCore| {
+        genome.mutate(&mut rng, CLIMB_RATE, 0.0, MutationKernel::Gaussian(CLIMB_STEP))
+    };
+
+    let (mut converged, mut escaped_higher, mut escaped_lower) = (0u32, 0u32, 0u32);
+    for sample in 0..samples {
+        // Cada arranque tiene su propio flujo: el resultado de uno no depende de los anteriores
+        let mut rng = pool.stream(sample as u64);
+        let start = LogGenome(champion_logs.0.map(|g| g + radius * rng.sample::<f64, _>(StandardNormal)));
+        // Sin empates: en los genes que no afectan al fitness el escalador derivaría sin rumbo
+        let (end, end_fitness) = hill_climb(start, iterations, false, &mut rng, neighbor, score);
+        if end.distance(&champion_logs) < tolerance {
+            converged += 1;
+        } else if end_fitness > *champion_fitness {
+            escaped_higher += 1;
+        } else {
+            escaped_lower += 1;
+        }
+    }
+
+    let fraction = converged as f64 / samples as f64;
+    let standard_error = (fraction * (1.0 - fraction) / samples as f64).sqrt();
+    println!("Convergen a la cuenca del campeón: {}/{} ({:.1}% ± {:.1}%)", converged, samples, fraction * 100.0, standard_error * 100.0);
+    println!("Escapan a un óptimo mejor: {}", escaped_higher);
+    println!("Escapan a un óptimo peor o igual: {}", escaped_lower);
+    Ok(())
+}
+
+// --- LÓGICA DEL ANÁLISIS DE SENSIBILIDAD GLOBAL (SOBOL) ---
+/// Estima los índices de Sobol de primer orden y de efecto total de cada gen con el esquema de
+/// Saltelli: dos matrices independientes `A` y `B` muestreadas uniformemente en los rangos activos
+/// (`--ranges`), y para cada gen `i` la matriz `AB_i` (la `A` con la columna `i` tomada de `B`). Los estimadores son
+/// los de Saltelli (2010) para el primer orden y de Jansen (1999) para el efecto total.
+fn run_sobol_mode(samples: u32, seed: Option<u64>, weights_file: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let weights = match weights_file {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    let seed = seed.unwrap_or_else(|| thread_rng().gen());
+    progress!("Semilla: {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let n = samples as usize;
+    let ranges = RangeConfig::active();
+
+    let mut sample_matrix = || -> Vec<[f64; GENE_COUNT]> {
+        (0..n).map(|_| ranges.0.map(|spec| rng.gen_range(spec.min..spec.max))).collect()
+    };
+    let a = sample_matrix();
+    let b = sample_matrix();
+    let evaluate = |row: &[f64; GENE_COUNT]| *calculate_fitness(&CosmicLaw::from_genes(*row), &weights).total;
+    let f_a: Vec<f64> = a.iter().map(evaluate).collect();
+    let f_b: Vec<f64> = b.iter().map(evaluate).collect();
+
+    let all: Vec<f64> = f_a.iter().chain(&f_b).copied().collect();
+    let mean = all.iter().sum::<f64>() / all.len() as f64;
+    let variance = all.iter().map(|f| (f - mean).powi(2)).sum::<f64>() / all.len() as f64;
+    progress!("Fitness medio {:.6}, varianza {:.6e} ({} evaluaciones)", mean, variance, n * (GENES.len() + 2));
+    if variance <= 0.0 {
+        return Err("El fitness no varía en la muestra: los índices de Sobol no están definidos".into());
+    }
+
+    let mut indices: Vec<(&str, f64, f64)> = Gene::ALL
+        .iter()
+        .map(|&gene| {
+            let i = gene as usize;
+            let (mut first_order, mut total) = (0.0, 0.0);
+            for k in 0..n {
+                let mut row = a[k];
+                row[i] = b[k][i];
+                let f_ab = evaluate(&row);
+                first_order += f_b[k] * (f_ab - f_a[k]);
+                total += (f_a[k] - f_ab).powi(2);
+            }
+            (ranges.0[i].name, first_order / n as f64 / variance, total / (2.0 * n as f64) / variance)
+        })
+        .collect();
+    indices.sort_by(|x, y| y.2.total_cmp(&x.2));
+
+    println!("{:<20} {:>12} {:>12}", "gen", "S_i", "S_Ti");
+    for (name, first_order, total) in &indices {
+        println!("{:<20} {:>12.4} {:>12.4}", name, first_order, total);
+    }
+    let first_order_sum: f64 = indices.iter().map(|(_, s, _)| s).sum();
+    println!("Suma de índices de primer orden: {:.4} (la varianza restante se debe a interacciones)", first_order_sum);
+    Ok(())
+}
+
+// --- LÓGICA DEL ANÁLISIS DE RUGOSIDAD ---
+/// Retardos de la autocorrelación que se imprimen además del retardo 1.
+const RUGGEDNESS_REPORTED_LAGS: [usize; 4] = [2, 5, 10, 50];
+
+/// Autocorrelación de la serie `values` al retardo `lag`, con la media y la varianza de toda la serie.
+fn autocorrelation(values: &[f64], mean: f64, variance: f64, lag: usize) -> f64 {
+    let pairs = values.len() - lag;
+    let covariance = values.iter().zip(&values[lag..])
+        .map(|(a, b)| (a - mean) * (b - mean))
+        .sum::<f64>() / pairs as f64;
+    covariance / variance
+}
+
+/// Refleja un log-gen en los bordes de su rango de muestreo, de modo que el paseo no sale de la caja.
+fn reflect_into(value: f64, spec: &GeneSpec) -> f64 {
+    let (low, high) = (spec.min.ln(), spec.max.ln());
+    let reflected = if value < low {
+        2.0 * low - value
+    } else if value > high {
+        2.0 * high - value
+    } else {
+        value
+    };
+    reflected.clamp(low, high)
+}
+
+fn run_ruggedness_mode(
+    genome_file: Option<&str>,
+    steps: u32,
+    step_size: f64,
+    seed: Option<u64>,
+    weights_file: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let start: CosmicLaw = match genome_file {
+        Some(path) => CosmicLaw::load(path)?,
+        None => REAL_UNIVERSE,
+    };
+    let weights = match weights_file {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    let seed = seed.unwrap_or_else(|| thread_rng().gen());
+    progress!("Semilla: {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // El genoma de partida se lleva dentro de la caja de muestreo antes de empezar
+    let ranges = RangeConfig::active();
+    let mut position = LogGenome::from_laws(&start);
+    for (value, spec) in position.0.iter_mut().zip(ranges.0.iter()) {
+        *value = reflect_into(*value, spec);
+    }
+    let mut fitness = Vec::with_capacity(steps as usize);
+    for _ in 0..steps {
+        fitness.push(*calculate_fitness(&position.to_laws(), &weights).total);
+        for (value, spec) in position.0.iter_mut().zip(ranges.0.iter()) {
+            *value = reflect_into(*value + step_size * rng.sample::<f64, _>(StandardNormal), spec);
+        }
+    }
+
+    let n = fitness.len() as f64;
+    let mean = fitness.iter().sum::<f64>() / n;
+    let variance = fitness.iter().map(|f| (f - mean).powi(2)).sum::<f64>() / n;
+    let viable = fitness.iter().filter(|&&f| f > 0.0).count();
+    progress!("Fitness medio {:.6}, varianza {:.6e}; {} de {} pasos viables", mean, variance, viable, steps);
+    if variance <= 0.0 {
+        return Err("El fitness no varía a lo largo del paseo: la autocorrelación no está definida (prueba otro genoma o un paso mayor)".into());
+    }
+
+    let rho_1 = autocorrelation(&fitness, mean, variance, 1);
+    // Longitud de correlación de Weinberger: distancia (en pasos) a la que la correlación cae a 1/e
+    // si el paisaje fuera un proceso AR(1)
+    let correlation_length = -1.0 / rho_1.abs().ln();
+    println!("Autocorrelación a retardo 1: {:.4}", rho_1);
+    for lag in RUGGEDNESS_REPORTED_LAGS.into_iter().filter(|&lag| lag < fitness.len()) {
+        println!("Autocorrelación a retardo {}: {:.4}", lag, autocorrelation(&fitness, mean, variance, lag));
+    }
+    println!("Longitud de correlación: {:.2} pasos de {} en logaritmo natural", correlation_length, step_size);
+    // La escala depende de `step_size`: solo tiene sentido comparar paisajes medidos con el mismo paso
+    let rating = if correlation_length < 2.0 {
+        "rugoso: los vecinos apenas predicen el fitness; conviene una búsqueda global (AG con hipermutación o reinicios)"
+    } else if correlation_length < 10.0 {
+        "moderado: hay estructura local aprovechable, pero con óptimos locales frecuentes"
+    } else {
+        "suave: el fitness varía poco entre vecinos; un optimizador local (PSO, escalador de colinas) debería bastar"
+    };
+    println!("Paisaje {}", rating);
+    Ok(())
+}
+
+// --- LÓGICA DEL ANÁLISIS DE AJUSTE FINO ---
+/// Máximo desplazamiento (en logaritmo natural) explorado en cada dirección: tres órdenes de magnitud.
+const FINE_TUNING_MAX_LOG_EXCURSION: f64 = 6.907_755_278_982_137;
+/// Primer paso (en logaritmo natural) de la búsqueda de la frontera.
+const FINE_TUNING_INITIAL_STEP: f64 = 1e-4;
+
+/// Desplazamiento logarítmico hasta la frontera en la que `keeps` deja de cumplirse, partiendo de 0
+/// en la dirección `direction` (±1). Primero duplica el paso hasta salir de la región y después
+/// biseca hasta `precision`. Asume que la región es contigua: un hueco más estrecho que el paso
+/// en el que se detecta la salida pasa desapercibido. `None` si no hay frontera a menos de
+/// `FINE_TUNING_MAX_LOG_EXCURSION`.
+fn find_log_boundary(direction: f64, precision: f64, keeps: impl Fn(f64) -> bool) -> Option<f64> {
+    let (mut inside, mut outside) = (0.0, FINE_TUNING_INITIAL_STEP);
+    while keeps(direction * outside) {
+        if outside >= FINE_TUNING_MAX_LOG_EXCURSION {
+            return None;
+        }
+        inside = outside;
+        outside = (outside * 2.0).min(FINE_TUNING_MAX_LOG_EXCURSION);
+    }
+    while outside - inside > precision {
+        let middle = 0.5 * (inside + outside);
+        if keeps(direction * middle) { inside = middle } else { outside = middle }
+    }
+    Some(inside)
+}
+
+fn run_fine_tuning_mode(genome_file: &str, precision: f64, weights_file: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let laws = CosmicLaw::load(genome_file)?;
+    let weights = match weights_file {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    let FitnessReport { total: fitness, level, .. } = calculate_fitness(&laws, &weights);
+    if *fitness <= 0.0 {
+        return Err(format!("El genoma es {}: no hay región viable que medir", classify_universe(&laws, fitness, level).to_lowercase()).into());
+    }
+    progress!("Genoma: fitness {:.6}, nivel {} ({})", *fitness, level, classify_universe(&laws, fitness, level));
+
+    // El universo "se conserva" si sigue siendo viable y alcanza al menos su nivel actual
+    let mut tunings: Vec<(&str, Option<f64>, Option<f64>)> = Gene::ALL.iter()
+        .map(|&gene| {
+            let value = laws.gene(gene);
+            let keeps = |log_offset: f64| {
+                let FitnessReport { total: fitness, level: new_level, .. } = calculate_fitness(&laws.with_gene(gene, value * log_offset.exp()), &weights);
+                *fitness > 0.0 && new_level >= level
+            };
+            (gene.spec().name, find_log_boundary(-1.0, precision, keeps), find_log_boundary(1.0, precision, keeps))
+        })
+        .collect();
+
+    // Fracción de ajuste: anchura del intervalo relativa al valor (sin frontera cuenta como la excursión máxima)
+    let tuning_fraction = |down: Option<f64>, up: Option<f64>| {
+        up.unwrap_or(FINE_TUNING_MAX_LOG_EXCURSION).exp() - (-down.unwrap_or(FINE_TUNING_MAX_LOG_EXCURSION)).exp()
+    };
+    tunings.sort_by(|a, b| tuning_fraction(a.1, a.2).total_cmp(&tuning_fraction(b.1, b.2)));
+
+    let bound = |offset: Option<f64>, direction: f64| match offset {
+        Some(offset) => format!("{:+.3}%", ((direction * offset).exp() - 1.0) * 100.0),
+        None => "sin límite".to_string(),
+    };
+    println!("{:<20} {:>12} {:>12} {:>14}", "constante", "bajada", "subida", "fracción");
+    for (name, down, up) in &tunings {
+        // Sin frontera en algún lado, la fracción es solo una cota inferior
+        let open = if down.is_none() || up.is_none() { "≥ " } else { "" };
+        let fraction = format!("{}{:.6}", open, tuning_fraction(*down, *up));
+        println!("{:<20} {:>12} {:>12} {:>14}", name, bound(*down, -1.0), bound(*up, 1.0), fraction);
+    }
+    let (name, down, up) = tunings[0];
+    println!(
+        "Este universo está ajustado sobre todo en `{}`: conserva el nivel {} solo entre {} y {} de su valor.",
+        name, level, bound(down, -1.0), bound(up, 1.0)
+    );
+    Ok(())
+}
+
+// --- LÓGICA DEL BOLETÍN DE NOTAS ---
+/// Puntos del barrido con el que se busca la constante más ajustada del boletín.
+const REPORT_CARD_SWEEP_STEPS: u32 = 50;
+
+/// Nota de una sub-puntuación: A desde 0.9, B desde 0.75, C desde 0.5, D desde 0.25 y F por debajo.
+fn letter_grade(score: Score) -> char {
+    match *score {
+        s if s >= 0.9 => 'A',
+        s if s >= 0.75 => 'B',
+        s if s >= 0.5 => 'C',
+        s if s >= 0.25 => 'D',
+        _ => 'F',
+    }
+}
+
+fn run_report_card_mode(genome_file: &str, output: Option<&str>, weights_file: Option<&str>, latex: bool) -> Result<(), Box<dyn Error>> {
+    let laws = CosmicLaw::load(genome_file)?;
+    let weights = match weights_file {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    let card = if latex {
+        latex_genome_table(genome_file, &laws, &weights)?
+    } else {
+        markdown_report_card(genome_file, &laws, &weights)?
+    };
+
+    match output {
+        Some(path) => {
+            fs::write(path, &card)?;
+            progress!("Boletín guardado en {}", path);
+        }
+        None => print!("{}", card),
+    }
+    Ok(())
+}
+
+fn markdown_report_card(genome_file: &str, laws: &CosmicLaw, weights: &FitnessWeights) -> Result<String, std::fmt::Error> {
+    let FitnessReport { total: fitness, level, .. } = calculate_fitness(laws, weights);
+    let summary = PhysicsEngine::new(laws.clone(), PhysicalConstants::default()).summary();
+
+    let mut card = String::new();
+    writeln!(card, "# Boletín de notas: {}", genome_file)?;
+    writeln!(card)?;
+    writeln!(card, "**Clasificación:** {} (nivel {}) — fitness {:.4}", classify_universe(laws, fitness, level), level, *fitness)?;
+    writeln!(card)?;
+    writeln!(card, "| Dominio | Puntuación | Nota |")?;
+    writeln!(card, "|---|---:|:---:|")?;
+    for (domain, score) in summary.domains() {
+        writeln!(card, "| {} | {:.3} | {} |", domain, *score, letter_grade(score))?;
+    }
+    writeln!(card)?;
+    // La constante más ajustada es la de menor fracción habitable manteniendo el nivel alcanzado
+    if level > 0 {
+        let (name, fraction) = habitable_fractions(laws, weights, REPORT_CARD_SWEEP_STEPS, level)[0];
+        writeln!(card, "**Constante más ajustada:** `{}` (solo el {:.1}% de su rango logarítmico conserva el nivel {})", name, fraction * 100.0, level)?;
+    } else {
+        writeln!(card, "**Constante más ajustada:** no aplica a un universo estéril")?;
+    }
+    Ok(card)
+}
+
+// --- LÓGICA DEL MODO INSPECCIÓN ---
+/// Lo que imprime `inspect`: las magnitudes intermedias del motor junto al `FitnessReport` de la escalera.
+#[derive(Debug, Serialize)]
+struct GenomeInspection {
+    mass_proton: f64,
+    mass_neutron: f64,
+    /// `(m_n − m_p − m_e)·c²` (J): el hidrógeno es estable frente a la captura electrónica si es positivo.
+    hydrogen_stability_margin: f64,
+    bohr_radius: f64,
+    alpha: f64,
+    deuteron_binding_energy: f64,
+    chandrasekhar_mass: f64,
+    #[serde(flatten)]
+    report: FitnessReport,
+    classification: &'static str,
+    /// Fitness medio bajo perturbaciones del ~1% (`robustness_score`), sembradas con la huella del
+    /// genoma como en `evolve --objective robustness`.
+    robustness: f64,
+}
+
+impl GenomeInspection {
+    fn new(laws: &CosmicLaw, weights: &FitnessWeights, robustness_samples: usize) -> Self {
+        let engine = PhysicsEngine::new(laws.clone(), PhysicalConstants::default());
+        let report = evaluate_engine_ladder(&engine, weights, MAX_COMPLEXITY_LEVEL);
+        let mass_proton = laws.proton_mass();
+        let mass_neutron = laws.neutron_mass();
+        GenomeInspection {
+            mass_proton,
+            mass_neutron,
+            hydrogen_stability_margin: (mass_neutron - mass_proton - laws.mass_electron) * engine.constants.c.powi(2),
+            bohr_radius: engine.bohr_radius(),
+            alpha: engine.alpha,
+            deuteron_binding_energy: engine.deuteron_binding_energy(),
+            chandrasekhar_mass: engine.chandrasekhar_mass(),
+            report,
+            classification: classify_universe(laws, report.total, report.level),
+            robustness: engine.robustness_score(weights, robustness_samples, &mut StdRng::seed_from_u64(laws.fingerprint())),
+        }
+    }
+}
+
+fn run_inspect_mode(genome_file: &str, json: bool, latex: bool, robustness_samples: usize, weights_file: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let laws = CosmicLaw::load(genome_file)?;
+    let weights = match weights_file {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    if latex {
+        print!("{}", latex_genome_table(genome_file, &laws, &weights)?);
+        return Ok(());
+    }
+    let inspection = GenomeInspection::new(&laws, &weights, robustness_samples);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&inspection)?);
+        return Ok(());
+    }
+
+    println!("--- INSPECCIÓN DE {} ---", genome_file);
+    println!("Masa del protón:              {:e} kg", inspection.mass_proton);
+    println!("Masa del neutrón:             {:e} kg", inspection.mass_neutron);
+    println!("Margen de estabilidad del H:  {:e} J", inspection.hydrogen_stability_margin);
+    println!("Radio de Bohr:                {:e} m", inspection.bohr_radius);
+    println!("Constante de estructura fina: {:.6} (1/{:.2})", inspection.alpha, 1.0 / inspection.alpha);
+    println!("Enlace del deuterón:          {:e} J", inspection.deuteron_binding_energy);
+    println!("Masa de Chandrasekhar:        {:e} kg", inspection.chandrasekhar_mass);
+    println!();
+    // Los términos posteriores a la barrera no se evalúan en la escalera; se muestran igualmente
+    let report = &inspection.report;
+    let all_scores = ladder_term_scores(&PhysicsEngine::new(laws, PhysicalConstants::default()));
+    println!("{:<22} {:>5} {:>10} {:>7} {:>12}", "término", "nivel", "puntuación", "peso", "contribución");
+    for (term, score) in report.terms.iter().zip(all_scores) {
+        println!(
+            "{:<22} {:>5} {:>10.4} {:>7.3} {:>12.4}{}",
+            term.name, term.level, *score, term.weight, term.weight * *score,
+            if term.score.is_some() { "" } else { "  (no cuenta: barrera anterior)" }
+        );
+    }
+    println!();
+    println!("Fitness: {:.6}", *report.total);
+    println!("Nivel: {} ({})", report.level, inspection.classification);
+    println!("Barrera: {}", report.gate.description());
+    println!("Robustez ({} perturbaciones): {:.6}", robustness_samples, inspection.robustness);
+    Ok(())
+}
+
+// --- EXPORTACIÓN A LATEX ---
+/// Símbolo (en modo matemático) y unidades (`siunitx`) de cada gen; sin unidades si es adimensional.
+fn gene_latex(gene: Gene) -> (&'static str, &'static str) {
+    match gene {
+        Gene::G => ("$G$", r"\si{\metre\cubed\per\kilogram\per\second\squared}"),
+        Gene::E => ("$e$", r"\si{\coulomb}"),
+        Gene::AlphaS => (r"$\alpha_s$", ""),
+        Gene::AlphaW => (r"$\alpha_w$", ""),
+        Gene::MassUpQuark => ("$m_u$", r"\si{\kilogram}"),
+        Gene::MassDownQuark => ("$m_d$", r"\si{\kilogram}"),
+        Gene::MassElectron => ("$m_e$", r"\si{\kilogram}"),
+        Gene::MassCharmQuark => ("$m_c$", r"\si{\kilogram}"),
+        Gene::MassStrangeQuark => ("$m_s$", r"\si{\kilogram}"),
+        Gene::MassMuon => (r"$m_\mu$", r"\si{\kilogram}"),
+        Gene::MassTopQuark => ("$m_t$", r"\si{\kilogram}"),
+        Gene::MassBottomQuark => ("$m_b$", r"\si{\kilogram}"),
+        Gene::MassTauon => (r"$m_\tau$", r"\si{\kilogram}"),
+        Gene::OmegaDm => (r"$\Omega_\mathrm{dm}$", ""),
+    }
+}
+
+/// Símbolo y unidades de cada observable de `PhysicsEngine::observables`.
+fn observable_latex(name: &str) -> (&'static str, &'static str) {
+    match name {
+        "alpha" => (r"$\alpha$", ""),
+        "bohr_radius" => ("$a_0$", r"\si{\metre}"),
+        "chandrasekhar_mass" => (r"$M_\mathrm{Ch}$", r"\si{\kilogram}"),
+        "deuteron_binding" => ("$B_d$", r"\si{\joule}"),
+        "proton_mass" => ("$m_p$", r"\si{\kilogram}"),
+        "neutron_proton_gap" => ("$m_n - m_p$", r"\si{\kilogram}"),
+        "muon_electron_ratio" => (r"$m_\mu / m_e$", ""),
+        "tau_muon_ratio" => (r"$m_\tau / m_\mu$", ""),
+        _ => ("", ""),
+    }
+}
+
+/// Escapa los caracteres especiales de LaTeX en texto normal (fuera del modo matemático).
+fn latex_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str(r"\textasciitilde{}"),
+            '^' => escaped.push_str(r"\textasciicircum{}"),
+            '\\' => escaped.push_str(r"\textbackslash{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Número en notación científica para `\num{}`; un valor no finito se escribe como una raya.
+fn latex_num(value: f64, digits: usize) -> String {
+    if value.is_finite() {
+        format!(r"\num{{{:.*e}}}", digits, value)
+    } else {
+        "---".to_string()
+    }
+}
+
+/// Tabla `tabular` con los genes, los observables derivados (ambos con su cociente frente a nuestro
+/// universo) y las sub-puntuaciones del motor. Usa `\num` y `\si` de `siunitx`.
+fn latex_genome_table(genome_file: &str, laws: &CosmicLaw, weights: &FitnessWeights) -> Result<String, std::fmt::Error> {
+    let FitnessReport { total: fitness, level, .. } = calculate_fitness(laws, weights);
+    let engine = PhysicsEngine::new(laws.clone(), PhysicalConstants::default());
+    let reference = PhysicsEngine::from_real_universe();
+
+    let mut table = String::new();
+    // El nombre del archivo va en un comentario: un salto de línea terminaría el comentario
+    writeln!(table, "% Genoma: {}", genome_file.replace(['\n', '\r'], " "))?;
+    writeln!(table, r"\begin{{tabular}}{{llll}}")?;
+    writeln!(table, r"\hline")?;
+    writeln!(table, r"Magnitud & Valor & Unidades & $\times$ real \\")?;
+    writeln!(table, r"\hline")?;
+    for &gene in &Gene::ALL {
+        let (symbol, unit) = gene_latex(gene);
+        let value = laws.gene(gene);
+        let ratio = value / REAL_UNIVERSE.gene(gene);
+        writeln!(table, r"{} & {} & {} & {} \\", symbol, latex_num(value, 4), unit, latex_num(ratio, 3))?;
+    }
+    writeln!(table, r"\hline")?;
+    for ((name, value), (_, expected)) in engine.observables().into_iter().zip(reference.observables()) {
+        let (symbol, unit) = observable_latex(name);
+        writeln!(table, r"{} & {} & {} & {} \\", symbol, latex_num(value, 4), unit, latex_num(value / expected, 3))?;
+    }
+    writeln!(table, r"\hline")?;
+    for (domain, score) in engine.summary().domains() {
+        writeln!(table, r"Puntuación: {} & \num{{{:.3}}} & & \\", latex_escape(domain), *score)?;
+    }
+    writeln!(table, r"Fitness (nivel {}, {}) & \num{{{:.4}}} & & \\", level, latex_escape(classify_universe(laws, fitness, level)), *fitness)?;
+    writeln!(table, r"\hline")?;
+    writeln!(table, r"\end{{tabular}}")?;
+    Ok(table)
+}
+
+// --- LÓGICA DEL PERFIL DE UNA CONSTANTE ---
+fn run_profile_mode(
+    gene: Gene,
+    genome_file: Option<&str>,
+    steps: u32,
+    precision: f64,
+    output: &str,
+    weights_file: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    let reference: CosmicLaw = match genome_file {
+        Some(path) => CosmicLaw::load(path)?,
+        None => REAL_UNIVERSE,
+    };
+    let weights = match weights_file {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    let spec = RangeConfig::active().spec(gene);
+    let evaluate = |value: f64| calculate_fitness(&reference.with_gene(gene, value), &weights);
+
+    let mut wtr = csv::Writer::from_path(output)?;
+    let config = serde_json::json!({ "gene": spec.name, "genome": genome_file, "steps": steps, "precision": precision, "output": output });
+    write_run_metadata([output], "profile", &config, None, Some(&weights))?;
+    wtr.write_record([spec.name, "fitness", "level"])?;
+    let mut points: Vec<(f64, u8)> = Vec::with_capacity(steps as usize);
+    for value in log_sweep(spec, steps) {
+        let FitnessReport { total: fitness, level, .. } = evaluate(value);
+        wtr.write_record([csv_float(value), csv_float(*fitness), level.to_string()])?;
+        points.push((value, level));
+    }
+    wtr.flush()?;
+
+    // Cada cambio de nivel entre puntos vecinos se refina por bisección en escala logarítmica.
+    // Dos transiciones dentro del mismo paso se cancelan y pasan desapercibidas.
+    let level_at = |value: f64| evaluate(value).level;
+    let reference_value = reference.gene(gene);
+    progress!("Valor de referencia: {} = {:e} (nivel {})", spec.name, reference_value, level_at(reference_value));
+    let mut transitions = 0;
+    for window in points.windows(2) {
+        let ((mut low, low_level), (mut high, high_level)) = (window[0], window[1]);
+        if low_level == high_level {
+            continue;
+        }
+        while (high / low).ln() > precision {
+            let middle = (low * high).sqrt();
+            if level_at(middle) == low_level { low = middle } else { high = middle }
+        }
+        let boundary = (low * high).sqrt();
+        transitions += 1;
+        println!(
+            "Nivel {} → {} en {} = {:e} ({:.4}× el de referencia)",
+            low_level, high_level, spec.name, boundary, boundary / reference_value
+        );
+    }
+    if transitions == 0 {
+        println!("El nivel no cambia en todo el rango de {} ({:e} a {:e})", spec.name, spec.min, spec.max);
+    }
+    progress!("Perfil de {} puntos guardado en {}", steps, output);
+    Ok(())
+}
+
+// --- LÓGICA DEL MODO BARRIDO ---
+fn run_sweep_mode(
+    genome_file: &str,
+    gene: Gene,
+    min: f64,
+    max: f64,
+    steps: u32,
+    output: &str,
+    weights_file: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    if min >= max {
+        return Err(format!("--min ({:e}) debe ser menor que --max ({:e})", min, max).into());
+    }
+    let base = CosmicLaw::load(genome_file)?;
+    let weights = match weights_file {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    let name = gene.spec().name;
+
+    let mut wtr = csv::Writer::from_path(output)?;
+    let config = serde_json::json!({ "genome": genome_file, "param": name, "min": min, "max": max, "steps": steps, "output": output });
+    write_run_metadata([output], "sweep", &config, None, Some(&weights))?;
+    wtr.write_record(["param_value", "fitness", "level"])?;
+    let mut best = (min, f64::NEG_INFINITY);
+    for i in 0..steps {
+        let t = i as f64 / (steps - 1) as f64;
+        let value = min * (1.0 - t) + max * t;
+        let FitnessReport { total: fitness, level, .. } = calculate_fitness(&base.with_gene(gene, value), &weights);
+        wtr.write_record([csv_float(value), csv_float(*fitness), level.to_string()])?;
+        if *fitness > best.1 {
+            best = (value, *fitness);
+        }
+    }
+    wtr.flush()?;
+    progress!("Máximo del barrido: {} = {:e} (fitness {:.6})", name, best.0, best.1);
+    progress!("Barrido de {} puntos de {} guardado en {}", steps, name, output);
+    Ok(())
+}
+
+// --- LÓGICA DE LA COLECCIÓN DE GENOMAS POR NIVEL ---
+const SUITE_LEVELS: usize = 5;
+
+/// Guía de la búsqueda dirigida: suma ponderada de los logaritmos de todas las sub-puntuaciones de
+/// la escalera, sin sus barreras. El fitness es plano por debajo de cada barrera (no cuentan las
+/// sub-puntuaciones que aún no se han alcanzado) y, en escala lineal, una puntuación en la cola de
+/// su sigmoide (p. ej. un enlace nuclear de 1e-18) no pesa nada frente a las demás; en escala
+/// logarítmica su pendiente se conserva y maximizar la guía exige subirlas todas a la vez.
+fn ladder_log_score(laws: &CosmicLaw, weights: &FitnessWeights) -> f64 {
+    let engine = PhysicsEngine::new(laws.clone(), PhysicalConstants::default());
+    let log = |score: Score| score.max(f64::MIN_POSITIVE).ln();
+    weights.atomic * log(engine.atomic_stability_score())
+        + weights.electromagnetic * log(engine.electromagnetic_score())
+        + weights.nuclear * log(engine.nuclear_stability_score())
+        + weights.stellar * (log(engine.calculate_stellar_viability()) + log(engine.dark_matter_structure_score()))
+        + weights.heavy_elements * log(engine.heavy_elements_viability())
+        + weights.reproductive * log(engine.calculate_black_hole_potential())
+}
+
+fn run_generate_suite_mode(
+    dir: &str,
+    samples: u64,
+    attempts: u64,
+    iterations: u32,
+    seed: Option<u64>,
+    weights_file: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    // Paso local del escalador, el mismo que en la cuenca de atracción
+    const CLIMB_RATE: f64 = 0.3;
+    const CLIMB_STEP: f64 = 0.05;
+
+    let weights = match weights_file {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    let base_seed = seed.unwrap_or_else(|| thread_rng().gen());
+    progress!("Semilla de la búsqueda: {}", base_seed);
+    let pool = RngPool::new(base_seed);
+    let mut suite: [Option<(CosmicLaw, Fitness)>; SUITE_LEVELS] = Default::default();
+
+    // Fase 1: muestreo uniforme. Los niveles bajos aparecen enseguida; del resto nos quedamos con el
+    // genoma con mejor guía (`ladder_log_score`) como punto de partida de la búsqueda dirigida
+    let mut best: Option<(CosmicLaw, f64)> = None;
+    for index in 0..samples {
+        let laws = CosmicLaw::random(&mut pool.stream(index));
+        let FitnessReport { total: fitness, level, .. } = calculate_fitness(&laws, &weights);
+        if suite[level as usize].is_none() {
+            suite[level as usize] = Some((laws.clone(), fitness));
+        }
+        let score = ladder_log_score(&laws, &weights);
+        if best.as_ref().is_none_or(|(_, best_score)| score > *best_score) {
+            best = Some((laws, score));
+        }
+        if suite.iter().all(Option::is_some) {
+            break;
+        }
+    }
+    let found = suite.iter().filter(|slot| slot.is_some()).count();
+    progress!("Muestreo: {} de {} niveles encontrados", found, SUITE_LEVELS);
+
+    // Fase 2: los niveles 3 y 4 son demasiado raros para el muestreo. Cada intento escala la guía
+    // desde el mejor genoma muestreado, y cualquier estado aceptado de un nivel que falte entra en
+    // la colección
+    if let Some((start, _)) = best {
+        let start = LogGenome::from_laws(&start);
+        for attempt in 0..attempts {
+            if suite.iter().all(Option::is_some) {
+                break;
+            }
+            let mut rng = pool.stream(samples + attempt);
+            let mut current = start;
+            let mut current_score = ladder_log_score(&current.to_laws(), &weights);
+            for _ in 0..iterations {
+                let candidate = current.mutate(&mut rng, CLIMB_RATE, 0.0, MutationKernel::Gaussian(CLIMB_STEP));
+                let laws = candidate.to_laws();
+                let score = ladder_log_score(&laws, &weights);
+                if score <= current_score {
+                    continue;
+                }
+                let FitnessReport { total: fitness, level, .. } = calculate_fitness(&laws, &weights);
+                if suite[level as usize].is_none() {
+                    progress!("Intento {}: nivel {} alcanzado (fitness {:.6})", attempt + 1, level, *fitness);
+                    suite[level as usize] = Some((laws, fitness));
+                }
+                current = candidate;
+                current_score = score;
+            }
+        }
+    }
+
+    // Cada genoma se vuelve a evaluar desde su JSON antes de darlo por bueno, para que la colección
+    // sirva de fijación: lo que se guarda es exactamente lo que se verificó
+    fs::create_dir_all(dir)?;
+    let mut outputs = Vec::new();
+    for (level, slot) in suite.iter().enumerate() {
+        let Some((laws, fitness)) = slot else { continue };
+        let path = std::path::Path::new(dir).join(format!("level_{}.json", level)).to_string_lossy().into_owned();
+        write_json_atomically(&path, laws)?;
+        let reloaded = CosmicLaw::load(&path)?;
+        let FitnessReport { total: reloaded_fitness, level: reloaded_level, .. } = calculate_fitness(&reloaded, &weights);
+        if reloaded_level as usize != level || reloaded_fitness != *fitness {
+            return Err(format!("{}: al releerlo da nivel {} (fitness {}), no nivel {}", path, reloaded_level, *reloaded_fitness, level).into());
+        }
+        println!("Nivel {}: {} (fitness {:.6}, {})", level, path, **fitness, classify_universe(laws, *fitness, reloaded_level));
+        outputs.push(path);
+    }
+    let config = serde_json::json!({ "dir": dir, "samples": samples, "attempts": attempts, "iterations": iterations });
+    write_run_metadata(outputs.iter().map(String::as_str), "generate-suite", &config, Some(base_seed), Some(&weights))?;
+
+    let missing: Vec<String> = (0..SUITE_LEVELS).filter(|&level| suite[level].is_none()).map(|level| level.to_string()).collect();
+    if !missing.is_empty() {
+        return Err(format!("no se encontró ningún genoma de nivel {}; prueba con más --samples, --attempts o --iterations, o con otras --tolerances o --weights", missing.join(", ")).into());
+    }
+    Ok(())
+}
+
+// --- LÓGICA DEL MODO TOPOLOGÍA DE MASAS ---
+/// Permutaciones de `0..n` en el orden de Steinhaus–Johnson–Trotter: cada una se obtiene de la
+/// anterior intercambiando dos posiciones vecinas (un código Gray de permutaciones).
+fn adjacent_transposition_permutations(n: usize) -> Vec<Vec<usize>> {
+    if n <= 1 {
+        return vec![(0..n).collect()];
+    }
+    let mut permutations = Vec::new();
+    // El elemento nuevo barre las posiciones de derecha a izquierda y de vuelta, alternando
+    for (index, smaller) in adjacent_transposition_permutations(n - 1).into_iter().enumerate() {
+        let positions: Vec<usize> = if index % 2 == 0 { (0..n).rev().collect() } else { (0..n).collect() };
+        for position in positions {
+            let mut permutation = smaller.clone();
+            permutation.insert(position, n - 1);
+            permutations.push(permutation);
+        }
+    }
+    permutations
+}
+
+/// Todas las combinaciones de `digits` dígitos en base `radix` en orden Gray reflejado: entre dos
+/// consecutivas cambia un único dígito, y en una unidad.
+fn reflected_mixed_radix(radix: usize, digits: usize) -> Vec<Vec<usize>> {
+    if digits == 0 {
+        return vec![Vec::new()];
+    }
+    let mut combinations = Vec::new();
+    for (index, prefix) in reflected_mixed_radix(radix, digits - 1).into_iter().enumerate() {
+        let values: Vec<usize> = if index % 2 == 0 { (0..radix).collect() } else { (0..radix).rev().collect() };
+        for value in values {
+            let mut combination = prefix.clone();
+            combination.push(value);
+            combinations.push(combination);
+        }
+    }
+    combinations
+}
+
+/// Nombre corto de un gen de masa para las etiquetas de ordenación (`mass_muon` → `muon`).
+fn mass_label(gene: Gene) -> &'static str {
+    let name = gene.spec().name;
+    name.strip_prefix("mass_").unwrap_or(name)
+}
+
+/// Recorre todas las ordenaciones de masas de las familias permutables. Cada familia conserva sus
+/// tres valores de masa del genoma de referencia y sólo cambia qué generación lleva cada uno; la
+/// ordenación `[k0, k1, k2]` da la masa más ligera a la generación `k0`. Las combinaciones siguen
+/// un código Gray, así que cada fila difiere de la anterior en un intercambio de dos masas vecinas
+/// de una sola familia.
+fn run_topology_mode(genome_file: Option<&str>, output: &str, min_level: u8, weights_file: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let reference: CosmicLaw = match genome_file {
+        Some(path) => CosmicLaw::load(path)?,
+        None => REAL_UNIVERSE,
+    };
+    let weights = match weights_file {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    let families: Vec<Vec<Gene>> = GeneCategory::SWAPPABLE.iter().map(|family| family.genes().collect()).collect();
+    let generations = families[0].len();
+    let orderings = adjacent_transposition_permutations(generations);
+    let family_masses: Vec<Vec<f64>> = families.iter()
+        .map(|genes| {
+            let mut masses: Vec<f64> = genes.iter().map(|&gene| reference.gene(gene)).collect();
+            masses.sort_by(f64::total_cmp);
+            masses
+        })
+        .collect();
+    let ordering_label = |family: usize, ordering: &[usize]| {
+        ordering.iter().map(|&generation| mass_label(families[family][generation])).collect::<Vec<_>>().join("<")
+    };
+
+    let mut wtr = csv::Writer::from_path(output)?;
+    let config = serde_json::json!({ "genome": genome_file, "output": output, "min_level": min_level });
+    write_run_metadata([output], "topology", &config, None, Some(&weights))?;
+    let mut header = vec!["step"];
+    header.extend(GeneCategory::SWAPPABLE.iter().map(|family| family.name()));
+    header.extend(["fitness", "level", "class"]);
+    wtr.write_record(&header)?;
+
+    let mut viable: Vec<(String, Fitness, u8)> = Vec::new();
+    let combinations = reflected_mixed_radix(orderings.len(), families.len());
+    for (step, combination) in combinations.iter().enumerate() {
+        let mut laws = reference.clone();
+        for (family, &choice) in combination.iter().enumerate() {
+            for (rank, &generation) in orderings[choice].iter().enumerate() {
+                laws = laws.with_gene(families[family][generation], family_masses[family][rank]);
+            }
+        }
+        let FitnessReport { total: fitness, level, .. } = calculate_fitness(&laws, &weights);
+        let labels: Vec<String> = combination.iter().enumerate().map(|(family, &choice)| ordering_label(family, &orderings[choice])).collect();
+        let mut record = vec![step.to_string()];
+        record.extend(labels.iter().cloned());
+        record.extend([csv_float(*fitness), level.to_string(), classify_universe(&laws, fitness, level).to_string()]);
+        wtr.write_record(&record)?;
+        if level >= min_level {
+            viable.push((labels.join(", "), fitness, level));
+        }
+    }
+    wtr.flush()?;
+
+    println!("Ordenaciones viables (nivel >= {}): {}/{}", min_level, viable.len(), combinations.len());
+    viable.sort_by(|a, b| b.1.total_cmp(&a.1));
+    for (label, fitness, level) in &viable {
+        println!("  nivel {} fitness {:.6}  {}", level, **fitness, label);
+    }
+    progress!("Topología de {} ordenaciones guardada en {}", combinations.len(), output);
+    Ok(())
+}
+
+// --- LÓGICA DEL BARRIDO DE TOLERANCIAS ---
+/// Curva de fracción viable frente al factor de escala de las tolerancias. Todos los factores se
+/// evalúan sobre los mismos universos (números aleatorios comunes), así que las diferencias entre
+/// puntos de la curva se deben a las tolerancias y no al muestreo. Las tolerancias de partida son
+/// las activas, de modo que el barrido se compone con `--tolerances`.
+fn run_tolerance_sweep_mode(args: &ToleranceSweepArgs) -> Result<(), Box<dyn Error>> {
+    if args.min_factor >= args.max_factor {
+        return Err(format!("--min-factor ({}) debe ser menor que --max-factor ({})", args.min_factor, args.max_factor).into());
+    }
+    let weights = match &args.weights {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    let base_seed = args.seed.unwrap_or_else(|| thread_rng().gen());
+    let base = ToleranceConfig::active();
+    let (lo, hi) = (args.min_factor.ln(), args.max_factor.ln());
+    let factors = (0..args.steps).map(|i| (lo + (hi - lo) * i as f64 / (args.steps - 1) as f64).exp());
+
+    let mut wtr = csv::Writer::from_path(&args.output)?;
+    write_run_metadata([args.output.as_str()], "tolerance-sweep", args, Some(base_seed), Some(&weights))?;
+    wtr.write_record(["factor", "viable_fraction", "standard_error", "level_0", "level_1", "level_2", "level_3", "level_4"])?;
+    progress!("{:>8} {:>10} {:>10}  universos por nivel", "factor", "viable", "error");
+    let mut curve = Vec::with_capacity(args.steps as usize);
+    for factor in factors {
+        let tolerances = base.scaled(factor);
+        tolerances.validate()?;
+        let counts = (0..args.universes)
+            .into_par_iter()
+            .fold(|| [0u64; 5], |mut counts, i| {
+                let laws = CosmicLaw::random(&mut StdRng::seed_from_u64(universe_seed(base_seed, i)));
+                let engine = PhysicsEngine::new(laws, PhysicalConstants::default()).with_tolerances(tolerances);
+                let level = evaluate_engine_ladder(&engine, &weights, MAX_COMPLEXITY_LEVEL).level;
+                counts[level as usize] += 1;
+                counts
+            })
+            .reduce(|| [0u64; 5], |a, b| std::array::from_fn(|level| a[level] + b[level]));
+        let viable: u64 = counts[args.min_level as usize..].iter().sum();
+        let fraction = viable as f64 / args.universes as f64;
+        let standard_error = (fraction * (1.0 - fraction) / args.universes as f64).sqrt();
+        let mut record = vec![csv_float(factor), csv_float(fraction), csv_float(standard_error)];
+        record.extend(counts.iter().map(u64::to_string));
+        wtr.write_record(&record)?;
+        progress!("{:>8.3} {:>9.4}% {:>9.4}%  {:?}", factor, fraction * 100.0, standard_error * 100.0, counts);
+        curve.push((factor, fraction));
+    }
+    wtr.flush()?;
+
+    // Elasticidad media de la curva: cuánto cambia (en log) la fracción viable por cada e-folding
+    // del factor. Cerca de 0, las conclusiones apenas dependen de la elección de tolerancias
+    let positive: Vec<(f64, f64)> = curve.iter().filter(|&&(_, fraction)| fraction > 0.0).map(|&(factor, fraction)| (factor.ln(), fraction.ln())).collect();
+    if positive.len() >= 2 {
+        println!("Elasticidad de la fracción viable respecto a las tolerancias: {:.3}", least_squares_slope(&positive));
+    } else {
+        println!("Menos de dos factores con universos viables: no se puede estimar la elasticidad");
+    }
+    progress!("Curva de {} factores guardada en {}", args.steps, args.output);
+    Ok(())
+}
+
+// --- LÓGICA DEL MODO CALIBRACIÓN ---
+fn run_calibration_mode(genome_file: Option<&str>, iterations: u32, output: &str) -> Result<(), Box<dyn Error>> {
+    let reference: CosmicLaw = match genome_file {
+        Some(path) => CosmicLaw::load(path)?,
+        None => REAL_UNIVERSE,
+    };
+    let mut rng = thread_rng();
+
+    let initial = FitnessWeights::default();
+    let FitnessReport { total: initial_fitness, level: initial_level, .. } = calculate_fitness(&reference, &initial);
+    progress!("Pesos por defecto: fitness {:.6}, nivel {} ({})", *initial_fitness, initial_level, classify_universe(&reference, initial_fitness, initial_level));
+    if *initial_fitness <= 0.0 {
+        // Con la puerta de viabilidad cerrada el fitness es 0 para cualquier peso: no hay nada que optimizar
+        return Err(format!("El genoma de referencia es {}: su fitness es 0 con cualquier peso", classify_universe(&reference, initial_fitness, initial_level).to_lowercase()).into());
+    }
+
+    // Vecino: transfiere una fracción de peso de un componente a otro, de modo que la suma
+    // sigue siendo exactamente 1 y ningún peso se vuelve negativo.
+    let neighbor = |weights: &FitnessWeights, rng: &mut dyn RngCore| {
+        let mut w = weights.as_array();
+        let from = rng.gen_range(0..w.len());
+        let to = rng.gen_range(0..w.len());
+        let amount = w[from] * rng.gen_range(0.0..0.5);
+        w[from] -= amount;
+        w[to] += amount;
+        FitnessWeights::from_array(w)
+    };
+    let (calibrated, best) = hill_climb(initial, iterations, true, &mut rng, neighbor, |w| *calculate_fitness(&reference, w).total);
+    calibrated.validate()?;
+
+    let level = calculate_fitness(&reference, &calibrated).level;
+    progress!("Pesos calibrados: fitness {:.6}, nivel {} ({})", best, level, analyze_universe_type(level));
+    progress!("{:#?}", calibrated);
+    fs::write(output, serde_json::to_string_pretty(&calibrated)?)?;
+    progress!("Pesos guardados en {}", output);
+    Ok(())
+}
+
+// --- LÓGICA DEL MODO MATRIZ DE DISTANCIAS ---
+fn run_distance_matrix_mode(input: &str, output: &str) -> Result<(), Box<dyn Error>> {
+    let genomes: Vec<CosmicLaw> = serde_json::from_str(&fs::read_to_string(input)?)?;
+    for (index, laws) in genomes.iter().enumerate() {
+        laws.validate().map_err(|e| format!("{}: genoma {}: {}", input, index, e))?;
+    }
+    let n = genomes.len();
+    progress!("Calculando distancias entre {} genomas ({} pares)...", n, n * n.saturating_sub(1) / 2);
+
+    // Los logaritmos se calculan una sola vez por genoma y la matriz es simétrica con diagonal nula:
+    // solo se evalúa el triángulo superior, guardado en forma condensada (como `pdist` de scipy).
+    let logs: Vec<LogGenome> = genomes.iter().map(LogGenome::from_laws).collect();
+    let condensed_index = |i: usize, j: usize| n * i - i * (i + 1) / 2 + (j - i - 1);
+    let mut condensed = Vec::with_capacity(n * n.saturating_sub(1) / 2);
+    for i in 0..n {
+        for j in (i + 1)..n {
+            condensed.push(logs[i].distance(&logs[j]));
+        }
+    }
+
+    let mut wtr = csv::Writer::from_path(output)?;
+    write_run_metadata([output], "distance-matrix", &serde_json::json!({ "input": input, "output": output }), None, None)?;
+    wtr.write_record(std::iter::once("genome".to_string()).chain((0..n).map(|j| j.to_string())))?;
+    for i in 0..n {
+        let row = (0..n).map(|j| match i.cmp(&j) {
+            std::cmp::Ordering::Equal => 0.0,
+            std::cmp::Ordering::Less => condensed[condensed_index(i, j)],
+            std::cmp::Ordering::Greater => condensed[condensed_index(j, i)],
+        });
+        wtr.write_record(std::iter::once(i.to_string()).chain(row.map(csv_float)))?;
+    }
+    wtr.flush()?;
+    progress!("Matriz de distancias guardada en {}", output);
+    Ok(())
+}
+
+// --- TRAYECTORIA DEL CAMPEÓN ---
+fn open_trajectory(path: Option<&str>) -> Result<Option<csv::Writer<fs::File>>, Box<dyn Error>> {
+    let Some(path) = path else { return Ok(None) };
+    let mut wtr = csv::Writer::from_path(path)?;
+    wtr.write_record(["generation", "gene_name", "value"])?;
+    Ok(Some(wtr))
+}
+
+/// Una fila por gen: el formato "largo" que prefieren las bibliotecas de gráficos para animar.
+fn write_trajectory_rows(wtr: &mut csv::Writer<fs::File>, generation: u64, laws: &CosmicLaw) -> Result<(), Box<dyn Error>> {
+    for (spec, value) in GENES.iter().zip(laws.genes()) {
+        wtr.write_record([generation.to_string(), spec.name.to_string(), csv_float(value)])?;
+    }
+    Ok(())
+}
+
+// --- RACHAS DEL CAMPEÓN ---
+/// Detecta cuántas generaciones seguidas conserva un mismo genoma el primer puesto.
+#[derive(Default, Clone, Serialize, Deserialize)]
+struct ChampionStreaks {
+    current: Option<u64>,
+    current_start: u64,
+    current_len: u64,
+    longest_start: u64,
+    longest_len: u64,
+}
+
+impl ChampionStreaks {
+    fn observe(&mut self, generation: u64, champion: &CosmicLaw) {
+        let fingerprint = champion.fingerprint();
+        if self.current == Some(fingerprint) {
+            self.current_len += 1;
+        } else {
+            self.current = Some(fingerprint);
+            self.current_start = generation;
+            self.current_len = 1;
+        }
+        if self.current_len > self.longest_len {
+            self.longest_len = self.current_len;
+            self.longest_start = self.current_start;
+        }
+    }
+
+    fn report(&self) {
+        progress!(
+            "Racha más larga de un mismo campeón: {} generaciones (desde la generación {})",
+            self.longest_len, self.longest_start
+        );
+        progress!(
+            "El campeón final apareció en la generación {} y lo fue durante {} generaciones",
+            self.current_start, self.current_len
+        );
+    }
+}
+
+// --- MUTACIÓN ADAPTATIVA ---
+/// Factor de la tasa de mutación y de la probabilidad de hipermutación durante un estancamiento.
+const STAGNATION_BOOST: f64 = 2.0;
+/// Fracción del refuerzo que se conserva en cada generación tras salir del estancamiento.
+const STAGNATION_BOOST_DECAY: f64 = 0.5;
+
+/// Calendario de la mutación adaptativa (`--stagnation-window`): tras `window` generaciones sin que
+/// el campeón mejore en más de `epsilon`, las tasas de base se multiplican por `STAGNATION_BOOST`
+/// hasta que vuelve a mejorar; desde entonces el refuerzo decae geométricamente hacia 1.
+#[derive(Clone, Serialize, Deserialize)]
+struct MutationSchedule {
+    window: u64,
+    epsilon: f64,
+    best: f64,
+    stagnant_generations: u64,
+    boost: f64,
+}
+
+impl MutationSchedule {
+    fn new(window: u64, epsilon: f64) -> Self {
+        Self { window, epsilon, best: f64::NEG_INFINITY, stagnant_generations: 0, boost: 1.0 }
+    }
+
+    /// Añade el fitness del campeón de una generación; devuelve `true` si acaba de empezar un estancamiento.
+    fn observe(&mut self, fitness: f64) -> bool {
+        if fitness > self.best + self.epsilon {
+            self.best = fitness;
+            self.stagnant_generations = 0;
+        } else {
+            self.stagnant_generations += 1;
+        }
+        if self.stagnant_generations >= self.window {
+            self.boost = STAGNATION_BOOST;
+        } else {
+            self.boost = 1.0 + (self.boost - 1.0) * STAGNATION_BOOST_DECAY;
+        }
+        self.stagnant_generations == self.window
+    }
+
+    /// Tasa de mutación y probabilidad de hipermutación efectivas a partir de las de base.
+    fn rates(&self, mutation_rate: f64, hypermutation_chance: f64) -> (f64, f64) {
+        ((mutation_rate * self.boost).min(1.0), (hypermutation_chance * self.boost).min(1.0))
+    }
+}
+
+// --- DIAGNÓSTICO DE CONVERGENCIA ---
+/// Variación del fitness por debajo de la cual una ventana se considera plana.
+const CONVERGENCE_TOLERANCE: f64 = 1e-6;
+
+/// Estado de la curva de fitness dentro de la ventana reciente.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum ConvergenceState {
+    Improving,
+    Oscillating,
+    Converged,
+}
+
+impl ConvergenceState {
+    fn description(self) -> &'static str {
+        match self {
+            ConvergenceState::Improving => "mejorando",
+            ConvergenceState::Oscillating => "oscilando",
+            ConvergenceState::Converged => "estancada",
+        }
+    }
+}
+
+/// Clasifica la curva de fitness de las últimas `capacity` generaciones a partir de su tendencia
+/// (pendiente por mínimos cuadrados) y de su dispersión:
+/// - estancada si tanto el ascenso ajustado como la desviación típica son despreciables;
+/// - mejorando si la curva no retrocede nunca o el ascenso supera dos veces el ruido en torno a
+///   la tendencia;
+/// - oscilando en otro caso: hay retrocesos y no dominan sobre ellos las mejoras.
+#[derive(Clone, Serialize, Deserialize)]
+struct ConvergenceMonitor {
+    window: VecDeque<f64>,
+    capacity: usize,
+    state: Option<ConvergenceState>,
+}
+
+impl ConvergenceMonitor {
+    fn new(capacity: usize) -> Self {
+        Self { window: VecDeque::with_capacity(capacity), capacity, state: None }
+    }
+
+    /// Añade el fitness de una generación y devuelve el nuevo estado si ha cambiado.
+    fn observe(&mut self, fitness: f64) -> Option<ConvergenceState> {
+        if self.window.len() == self.capacity {
+            self.window.pop_front();
+        }
+        self.window.push_back(fitness);
+        if self.window.len() < self.capacity {
+            return None;
+        }
+        let state = self.classify();
+        (self.state.replace(state) != Some(state)).then_some(state)
+    }
+
+    fn classify(&self) -> ConvergenceState {
+        let n = self.window.len() as f64;
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = self.window.iter().sum::<f64>() / n;
+        let (mut sxy, mut sxx) = (0.0, 0.0);
+        for (x, y) in self.window.iter().enumerate() {
+            sxy += (x as f64 - mean_x) * (y - mean_y);
+            sxx += (x as f64 - mean_x).powi(2);
+        }
+        let slope = sxy / sxx;
+        let rise = slope * (n - 1.0);
+        let spread = (self.window.iter().map(|y| (y - mean_y).powi(2)).sum::<f64>() / n).sqrt();
+        let residual_noise = (self.window.iter().enumerate()
+            .map(|(x, y)| (y - mean_y - slope * (x as f64 - mean_x)).powi(2))
+            .sum::<f64>() / n).sqrt();
+        let regresses = self.window.iter().zip(self.window.iter().skip(1))
+            .any(|(previous, next)| next < &(previous - CONVERGENCE_TOLERANCE));
+
+        if spread <= CONVERGENCE_TOLERANCE && rise.abs() <= CONVERGENCE_TOLERANCE {
+            ConvergenceState::Converged
+        } else if !regresses || rise > 2.0 * residual_noise {
+            ConvergenceState::Improving
+        } else {
+            ConvergenceState::Oscillating
+        }
+    }
+
+    fn report(&self) {
+        match self.state {
+            Some(state) => progress!("Diagnóstico de convergencia (últimas {} generaciones): {}", self.capacity, state.description()),
+            None => progress!("Diagnóstico de convergencia: menos de {} generaciones, sin datos suficientes", self.capacity),
+        }
+    }
+}
+
+/// Punto de control con el mejor genoma encontrado hasta el momento (`--emit-seed-on-improvement`).
+const BEST_SO_FAR_FILE: &str = "best_so_far.json";
+
+/// Genoma campeón de una evolución (`--output`) con su fitness y su nivel. Los genes van en el
+/// nivel superior del objeto, así que el archivo sigue sirviendo de `--seed`: al leerlo como
+/// `CosmicLaw` se ignoran los demás campos.
+#[derive(Serialize)]
+struct ChampionRecord {
+    fitness: f64,
+    level: u8,
+    #[serde(flatten)]
+    laws: CosmicLaw,
+}
+
+impl ChampionRecord {
+    fn new(laws: CosmicLaw, weights: &FitnessWeights) -> Self {
+        let FitnessReport { total: fitness, level, .. } = calculate_fitness(&laws, weights);
+        Self { fitness: *fitness, level, laws }
+    }
+}
+
+/// Escribe `value` como JSON en un archivo temporal y lo renombra sobre `path`.
+/// El renombrado es atómico, así que una interrupción a mitad de escritura nunca deja `path` truncado.
+fn write_json_atomically(path: &str, value: &impl Serialize) -> Result<(), Box<dyn Error>> {
+    let tmp_path = format!("{}.tmp", path);
+    fs::write(&tmp_path, serde_json::to_string_pretty(value)?)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+// --- METADATOS DE LAS SALIDAS ---
+/// Procedencia de un CSV de salida, guardada junto a él en `<csv>.meta.json`.
+#[derive(Serialize)]
+struct RunMetadata<'a, T: Serialize> {
+    crate_version: &'static str,
+    mode: &'static str,
+    /// Segundos desde la época Unix.
+    timestamp: f64,
+    /// Argumentos efectivos del modo (incluidos los valores por defecto).
+    config: &'a T,
+    /// Semilla efectiva del generador aleatorio, si el modo es reproducible.
+    seed: Option<u64>,
+    weights: Option<&'a FitnessWeights>,
+    tolerances: ToleranceConfig,
+    stellar: StellarParams,
+    gene_weights: GeneWeights,
+    /// Rangos de muestreo e hipermutación de cada gen.
+    ranges: &'static RangeConfig,
+}
+
+/// Escribe el archivo de metadatos de cada CSV de `outputs`. Se usa un archivo aparte en lugar
+/// de un bloque de comentarios para no romper a los lectores de CSV que no los ignoran.
+fn write_run_metadata<'a>(
+    outputs: impl IntoIterator<Item = &'a str>,
+    mode: &'static str,
+    config: &impl Serialize,
+    seed: Option<u64>,
+    weights: Option<&FitnessWeights>,
+) -> Result<(), Box<dyn Error>> {
+    let metadata = RunMetadata {
+        crate_version: env!("CARGO_PKG_VERSION"),
+        mode,
+        timestamp: unix_timestamp(),
+        config,
+        seed,
+        weights,
+        tolerances: ToleranceConfig::active(),
+        stellar: StellarParams::active(),
+        gene_weights: *GeneWeights::active(),
+        ranges: RangeConfig::active(),
+    };
+    for output in outputs {
+        write_json_atomically(&format!("{}.meta.json", output), &metadata)?;
+    }
+    Ok(())
+}
+
+// --- MANIFIESTO DE EXPERIMENTO ---
+/// Receta completa de un experimento. Se escribe al empezar (sin huellas de las salidas) y se
+/// completa al terminar con el SHA-256 de cada salida, que es lo que `reproduce` verifica.
+#[derive(Serialize, Deserialize)]
+struct ExperimentManifest {
+    crate_version: String,
+    /// Commit del que se compiló el binario (`-dirty` si había cambios sin confirmar).
+    git_commit: String,
+    mode: String,
+    /// Segundos desde la época Unix.
+    timestamp: f64,
+    /// Argumentos que repiten el experimento, sin el ejecutable ni `--write-manifest` y con la
+    /// semilla efectiva aunque se eligiera al azar.
+    arguments: Vec<String>,
+    /// Configuración efectiva: argumentos del modo, semilla, pesos, tolerancias, parámetros
+    /// estelares, pesos por gen y rangos.
+    config: serde_json::Value,
+    outputs: Vec<OutputDigest>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct OutputDigest {
+    path: String,
+    sha256: Option<String>,
+}
+
+/// Claves de `ExperimentManifest::config` que deben coincidir al reproducir. Los argumentos del
+/// modo se excluyen: la semilla inyectada y la ruta del nuevo manifiesto cambian su forma.
+const MANIFEST_RECIPE_KEYS: [&str; 6] = ["seed", "weights", "tolerances", "stellar", "gene_weights", "ranges"];
+
+impl ExperimentManifest {
+    fn new<'a>(
+        mode: &str,
+        extra_arguments: impl IntoIterator<Item = String>,
+        args: &impl Serialize,
+        seed: u64,
+        weights: &FitnessWeights,
+        outputs: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let arguments = strip_option(std::env::args().skip(1), "--write-manifest")
+            .chain(extra_arguments)
+            .collect();
+        let config = serde_json::json!({
+            "args": args,
+            "seed": seed,
+            "weights": weights,
+            "tolerances": ToleranceConfig::active(),
+            "stellar": StellarParams::active(),
+            "gene_weights": GeneWeights::active(),
+            "ranges": RangeConfig::active(),
+        });
+        Ok(Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            git_commit: env!("GIT_COMMIT").to_string(),
+            mode: mode.to_string(),
+            timestamp: unix_timestamp(),
+            arguments,
+            config,
+            outputs: outputs.into_iter().map(|path| OutputDigest { path: path.to_string(), sha256: None }).collect(),
+        })
+    }
+
+    fn record_digests(&mut self) -> Result<(), Box<dyn Error>> {
+        for output in &mut self.outputs {
+            output.sha256 = Some(sha256_file(&output.path)?);
+        }
+        Ok(())
+    }
+}
+
+/// Quita de `arguments` la opción `name` y su valor (`--opción valor` o `--opción=valor`).
+fn strip_option(arguments: impl Iterator<Item = String>, name: &str) -> impl Iterator<Item = String> {
+    let inline_prefix = format!("{}=", name);
+    let name = name.to_string();
+    let mut skip_value = false;
+    arguments.filter(move |argument| {
+        if std::mem::take(&mut skip_value) {
+            return false;
+        }
+        if *argument == name {
+            skip_value = true;
+            return false;
+        }
+        !argument.starts_with(&inline_prefix)
+    })
+}
+
+/// SHA-256 (hexadecimal) del contenido de un archivo, leído por bloques.
+fn sha256_file(path: &str) -> Result<String, Box<dyn Error>> {
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Repite el experimento con el mismo binario (`current_exe`) y compara el manifiesto nuevo con el
+/// original: la receta, que explicaría cualquier diferencia, y la huella de cada salida.
+fn run_reproduce_mode(manifest_file: &str) -> Result<(), Box<dyn Error>> {
+    let original: ExperimentManifest = serde_json::from_str(&fs::read_to_string(manifest_file)?)?;
+    progress!("Experimento `{}` de la versión {} (commit {})", original.mode, original.crate_version, original.git_commit);
+    if original.crate_version != env!("CARGO_PKG_VERSION") || original.git_commit != env!("GIT_COMMIT") {
+        progress!(
+            "Aviso: este binario es la versión {} (commit {}); las salidas pueden diferir",
+            env!("CARGO_PKG_VERSION"), env!("GIT_COMMIT")
+        );
+    }
+    if original.outputs.iter().any(|output| output.sha256.is_none()) {
+        return Err(format!("El manifiesto {} no tiene las huellas de las salidas: el experimento original no terminó", manifest_file).into());
+    }
+
+    let rerun_manifest = format!("{}.reproduce.json", manifest_file);
+    progress!("Ejecutando: {}", original.arguments.join(" "));
+    let status = std::process::Command::new(std::env::current_exe()?)
+        .args(&original.arguments)
+        .args(["--write-manifest", &rerun_manifest])
+        .status()?;
+    if !status.success() {
+        return Err(format!("La repetición del experimento terminó con {}", status).into());
+    }
+    let rerun: ExperimentManifest = serde_json::from_str(&fs::read_to_string(&rerun_manifest)?)?;
+
+    for key in MANIFEST_RECIPE_KEYS {
+        if original.config.get(key) != rerun.config.get(key) {
+            progress!("Aviso: la configuración `{}` difiere de la del manifiesto (¿cambió algún archivo de entrada?)", key);
+        }
+    }
+    let mut mismatches = 0;
+    for output in &original.outputs {
+        let reproduced = rerun.outputs.iter().find(|rerun| rerun.path == output.path).and_then(|rerun| rerun.sha256.as_ref());
+        if reproduced == output.sha256.as_ref() {
+            println!("{}: coincide", output.path);
+        } else {
+            mismatches += 1;
+            println!("{}: DIFIERE", output.path);
+        }
+    }
+    if mismatches > 0 {
+        return Err(format!("{} de {} salidas no coinciden con el manifiesto (el nuevo está en {})", mismatches, original.outputs.len(), rerun_manifest).into());
+    }
+    fs::remove_file(&rerun_manifest)?;
+    println!("Experimento reproducido: las {} salidas coinciden byte a byte", original.outputs.len());
+    Ok(())
+}
+
+// --- LÓGICA DEL MODO MAPEO ---
+fn run_mapping_mode(args: &MapArgs) -> Result<(), Box<dyn Error>> {
+    let num_universes = args.universes;
+    let weights = match &args.weights {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    let phase_diagram = args.phase_diagram.as_ref()
+        .map(|_| PhaseDiagram::new(args.phase_x, args.phase_y, args.phase_bins));
+
+    PROFILING.store(args.profile, Ordering::Relaxed);
+    // Cada universo uniforme usa su propio generador derivado de (semilla base, índice), de modo
+    // que `replay` puede reconstruir cualquiera de ellos sin repetir el mapeo
+    let base_seed = args.seed.unwrap_or_else(|| thread_rng().gen());
+    let mut rng = StdRng::seed_from_u64(base_seed);
+    let mut latin_hypercube = args.stratified.then(|| LatinHypercube::new(num_universes));
+    let schema = if args.compat_csv { LandscapeSchema::Compat } else { LandscapeSchema::Full };
+    if args.compat_csv && args.format != LandscapeFormat::Csv {
+        return Err("--compat-csv solo admite --format csv".into());
+    }
+    let landscape_file = args.format.file_name();
+    let wtr = args.format.create(schema, args.detailed)?;
+    let outputs = std::iter::once(landscape_file).chain(args.phase_diagram.as_deref());
+    write_run_metadata(outputs, "map", args, Some(base_seed), Some(&weights))?;
+    let manifest = match &args.write_manifest {
+        Some(path) => {
+            if args.no_sampling && args.threads.is_some() {
+                return Err("--write-manifest no admite --no-sampling con --threads: el orden de las filas depende del reparto entre hilos".into());
+            }
+            let seed_arguments = args.seed.is_none().then(|| ["--seed".to_string(), base_seed.to_string()]);
+            let outputs = std::iter::once(landscape_file).chain(args.phase_diagram.as_deref()).chain(args.outcomes.as_deref());
+            let manifest = ExperimentManifest::new("map", seed_arguments.into_iter().flatten(), args, base_seed, &weights, outputs)?;
+            write_json_atomically(path, &manifest)?;
+            Some((path, manifest))
+        }
+        None => None,
+    };
+
+    progress!("Semilla base: {}", base_seed);
+    progress!("Simulando {} universos y muestreando uniformemente {} candidatos viables...", num_universes, args.sample_size);
+    // Sin muestreo, los universos elegibles se envían al hilo escritor en cuanto se evalúan
+    let (stream, sink) = if args.no_sampling {
+        let (sender, receiver) = mpsc::sync_channel(RAW_OUTPUT_CHANNEL_CAPACITY);
+        (Some(sender), LandscapeSink::Streamed(spawn_landscape_writer(wtr, receiver)))
+    } else {
+        (None, LandscapeSink::Sampled(wtr))
+    };
+
+    let mut tally = MappingTally { phase_diagram, ..MappingTally::default() };
+    let bar = progress_bar(num_universes, MAP_BAR_TEMPLATE);
+    let sample = match args.threads {
+        Some(threads) => {
+            // Con 0 hilos, rayon usa uno por núcleo lógico
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build()?;
+            progress!("Mapeo paralelo con {} hilos", pool.current_num_threads());
+            let mut sample = PrioritySample::new(args.sample_size, base_seed);
+            // Bloques de un millón: cada uno se reparte entre los hilos y se combina al terminar,
+            // de modo que los hitos de progreso se conservan
+            let mut start = 0;
+            while start < num_universes {
+                let end = (start + MAPPING_MILESTONE).min(num_universes);
+                let empty = || (tally.empty_like(), PrioritySample::new(args.sample_size, base_seed));
+                let (block_tally, block_sample) = pool.install(|| {
+                    (start..end).into_par_iter()
+                        .fold(empty, |(mut tally, mut sample), i| {
+                            let laws = CosmicLaw::random(&mut StdRng::seed_from_u64(universe_seed(base_seed, i)));
+                            if let Some(item) = map_universe(args, &weights, i, laws, &mut tally) {
+                                match &stream {
+                                    Some(sender) => send_to_writer(sender, item),
+                                    None => sample.offer(i, item),
+                                }
+                            }
+                            bar.inc(1);
+                            (tally, sample)
+                        })
+                        .reduce(empty, |(a, a_sample), (b, b_sample)| (a.merge(b), a_sample.merge(b_sample)))
+                });
+                tally = tally.merge(block_tally);
+                sample = sample.merge(block_sample);
+                start = end;
+                if end < num_universes {
+                    bar.suspend(|| report_mapping_milestone(args, end, tally.best_fitness));
+                }
+            }
+            UniverseSample::Priority(sample)
+        }
+        None => {
+            let mut sample = if args.deterministic {
+                UniverseSample::Priority(PrioritySample::new(args.sample_size, base_seed))
+            } else {
+                UniverseSample::Reservoir(Reservoir::new(args.sample_size))
+            };
+            for i in 0..num_universes {
+                let random_laws = match latin_hypercube.as_mut() {
+                    Some(lhs) => lhs.next_genome(&mut rng),
+                    None => CosmicLaw::random(&mut StdRng::seed_from_u64(universe_seed(base_seed, i))),
+                };
+                if let Some(item) = map_universe(args, &weights, i, random_laws, &mut tally) {
+                    match &stream {
+                        Some(sender) => send_to_writer(sender, item),
+                        None => sample.offer(i, item, &mut rng),
+                    }
+                }
+                bar.inc(1);
+                if i > 0 && i % MAPPING_MILESTONE == 0 {
+                    bar.suspend(|| report_mapping_milestone(args, i, tally.best_fitness));
+                }
+            }
+            sample
+        }
+    };
+    bar.finish_and_clear();
+    ProgressEvent::new("mapping", num_universes, tally.best_fitness).emit();
+
+    // Cerrar el canal para que el escritor termine
+    drop(stream);
+    let (sample_seen, written) = match sink {
+        LandscapeSink::Sampled(mut wtr) => {
+            let sample_seen = sample.seen();
+            let items = sample.into_items();
+            for item in &items {
+                wtr.write(item)?;
+            }
+            wtr.flush()?;
+            (sample_seen, items.len() as u64)
+        }
+        LandscapeSink::Streamed(writer) => {
+            let written = writer.join().map_err(|_| "El hilo escritor del paisaje terminó con un pánico")??;
+            (written, written)
+        }
+    };
+    progress!("--- MAPEO COMPLETADO ---");
+    let outcomes = &tally.outcomes;
+    progress!("{} universos viables encontrados", outcomes.viable_by_level.iter().sum::<u64>());
+    progress!("Universos viables por nivel: {:?}", outcomes.viable_by_level);
+    progress!("{} de los universos estériles son mundos de neutrones", outcomes.neutron_worlds);
+    tally.print_gate_breakdown();
+    if args.require_level > 0 {
+        progress!("{} universos de nivel >= {} elegibles para la muestra", sample_seen, args.require_level);
+    }
+    progress!("Datos de {} universos guardados en {}", written, landscape_file);
+    if let (Some(diagram), Some(path)) = (&tally.phase_diagram, &args.phase_diagram) {
+        diagram.write(path)?;
+        progress!("Diagrama de fases guardado en {}", path);
+    }
+    if let Some(path) = &args.outcomes {
+        fs::write(path, serde_json::to_string_pretty(outcomes)?)?;
+        progress!("Recuento de resultados guardado en {}", path);
+    }
+    if args.profile {
+        print_profile_report();
+    }
+    if args.profile_memory {
+        print_memory_report("Memoria al final del mapeo");
+    }
+    if let Some((path, mut manifest)) = manifest {
+        manifest.record_digests()?;
+        write_json_atomically(path, &manifest)?;
+        progress!("Manifiesto del experimento guardado en {}", path);
+    }
+    Ok(())
+}
+
+/// Filas en tránsito entre los evaluadores y el hilo escritor con `--no-sampling` (~10 MB).
+const RAW_OUTPUT_CHANNEL_CAPACITY: usize = 65_536;
+
+/// Destino de las filas del paisaje: la muestra se escribe al final; sin muestreo, un hilo
+/// escritor las vuelca según llegan.
+enum LandscapeSink {
+    Sampled(Box<dyn LandscapeWriter>),
+    Streamed(thread::JoinHandle<std::io::Result<u64>>),
+}
+
+/// Hilo consumidor del canal acotado: escribe cada universo recibido y devuelve cuántos escribió
+/// cuando todos los emisores se han cerrado.
+fn spawn_landscape_writer(
+    mut wtr: Box<dyn LandscapeWriter>,
+    receiver: mpsc::Receiver<MappedUniverse>,
+) -> thread::JoinHandle<std::io::Result<u64>> {
+    thread::spawn(move || {
+        let mut written = 0;
+        for item in receiver {
+            wtr.write(&item)?;
+            written += 1;
+        }
+        wtr.flush()?;
+        Ok(written)
+    })
+}
+
+/// Envía un universo al hilo escritor, bloqueando mientras el canal esté lleno. Si el escritor ha
+/// fallado, el envío se descarta: su error se recoge al final con `join`.
+fn send_to_writer(sender: &SyncSender<MappedUniverse>, item: MappedUniverse) {
+    let _ = sender.send(item);
+}
+
+/// Universos entre hitos de progreso del mapeo.
+const MAPPING_MILESTONE: u64 = 1_000_000;
+
+/// Un universo de la muestra del mapeo: índice, genoma, fitness y nivel.
+type MappedUniverse = (u64, CosmicLaw, Fitness, u8);
+
+/// Evalúa el universo `i`, lo anota en `tally` y lo devuelve si es elegible para la muestra del CSV.
+fn map_universe(args: &MapArgs, weights: &FitnessWeights, i: u64, laws: CosmicLaw, tally: &mut MappingTally) -> Option<MappedUniverse> {
+    const FITNESS_THRESHOLD_TO_LOG: f64 = 0.0;
+    let FitnessReport { total: fitness, level: winning_gen, gate, .. } = calculate_fitness_up_to(&laws, weights, args.max_level);
+    tally.record(&laws, fitness, winning_gen, gate);
+
+    // Añadir al modo mapping
+    if *fitness > FITNESS_THRESHOLD_TO_LOG {
+        let universe_type = analyze_universe_type(winning_gen);
+        if i.is_multiple_of(10_000_000) {
+            if args.compare_to_real {
+                progress!("Muestra #{}: Fitness {:.4}, Tipo: {} [{}]", i, *fitness, universe_type, relative_to_real(&laws));
+            } else {
+                progress!("Muestra #{}: Fitness {:.4}, Tipo: {}", i, *fitness, universe_type);
+            }
+        }
+    }
+
+    (*fitness > FITNESS_THRESHOLD_TO_LOG && winning_gen >= args.require_level)
+        .then_some((i, laws, fitness, winning_gen))
+}
+
+fn report_mapping_milestone(args: &MapArgs, mapped: u64, best_fitness: f64) {
+    progress!("... {} millones de universos mapeados.", mapped / MAPPING_MILESTONE);
+    ProgressEvent::new("mapping", mapped, best_fitness).emit();
+    if args.profile_memory {
+        print_memory_report("Memoria");
+    }
+}
+
+/// Resultados acumulados de un tramo del mapeo. Los tramos evaluados por distintos hilos se
+/// combinan con `merge`, que es conmutativo: el total no depende de cómo se repartió el trabajo.
+#[derive(Default)]
+struct MappingTally {
+    outcomes: MappingOutcomes,
+    phase_diagram: Option<PhaseDiagram>,
+    best_fitness: f64,
+    /// Universos por barrera de la escalera que los detuvo, indexados por `LadderGate::index`.
+    gate_counts: [u64; LadderGate::ALL.len()],
+}
+
+impl MappingTally {
+    fn record(&mut self, laws: &CosmicLaw, fitness: Fitness, level: u8, gate: LadderGate) {
+        self.best_fitness = self.best_fitness.max(*fitness);
+        self.gate_counts[gate.index()] += 1;
+        self.outcomes.record(laws, fitness, level);
+        if let Some(diagram) = self.phase_diagram.as_mut() {
+            diagram.record(laws, fitness, level);
+        }
+    }
+
+    /// Recuento vacío con la misma configuración (ejes y celdas del diagrama de fases).
+    fn empty_like(&self) -> Self {
+        let phase_diagram = self.phase_diagram.as_ref().map(|d| PhaseDiagram::new(d.x, d.y, d.bins));
+        Self { phase_diagram, ..Self::default() }
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        self.best_fitness = self.best_fitness.max(other.best_fitness);
+        self.outcomes.merge(&other.outcomes);
+        for (count, other_count) in self.gate_counts.iter_mut().zip(other.gate_counts) {
+            *count += other_count;
+        }
+        if let (Some(diagram), Some(other)) = (self.phase_diagram.as_mut(), other.phase_diagram) {
+            diagram.merge(&other);
+        }
+        self
+    }
+
+    /// Desglose de por qué se detuvo cada universo, de la barrera más letal a la menos.
+    fn print_gate_breakdown(&self) {
+        let total: u64 = self.gate_counts.iter().sum();
+        if total == 0 {
+            return;
+        }
+        let mut gates: Vec<(LadderGate, u64)> = LadderGate::ALL.iter()
+            .map(|&gate| (gate, self.gate_counts[gate.index()]))
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        gates.sort_by_key(|&(gate, count)| (std::cmp::Reverse(count), gate.index()));
+        progress!("Barrera que detuvo a cada universo:");
+        for (gate, count) in gates {
+            progress!("  {}: {:.1}% ({})", gate.description(), 100.0 * count as f64 / total as f64, count);
+        }
+    }
+}
+
+// --- RECUENTO DE RESULTADOS DEL MAPEO ---
+/// Estadísticos suficientes de un mapeo: como los universos son independientes, remuestrear los
+/// resultados individuales equivale a remuestrear estas categorías (distribución multinomial).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct MappingOutcomes {
+    /// Universos con fitness nulo.
+    sterile: u64,
+    /// Universos con fitness positivo, por nivel de complejidad.
+    viable_by_level: [u64; 5],
+    /// Universos estériles que son mundos de neutrones (incluidos en `sterile`). No es una
+    /// categoría del remuestreo: `bootstrap-ci` los trata como estériles.
+    #[serde(default)]
+    neutron_worlds: u64,
+}
+
+impl MappingOutcomes {
+    fn record(&mut self, laws: &CosmicLaw, fitness: Fitness, level: u8) {
+        if *fitness > 0.0 {
+            self.viable_by_level[(level as usize).min(4)] += 1;
+        } else {
+            self.sterile += 1;
+            if is_neutron_world(laws) {
+                self.neutron_worlds += 1;
+            }
+        }
+    }
+
+    fn merge(&mut self, other: &MappingOutcomes) {
+        self.sterile += other.sterile;
+        self.neutron_worlds += other.neutron_worlds;
+        for (count, other_count) in self.viable_by_level.iter_mut().zip(other.viable_by_level) {
+            *count += other_count;
+        }
+    }
+
+    fn total(&self) -> u64 {
+        self.sterile + self.viable_by_level.iter().sum::<u64>()
+    }
+
+    /// Categorías en orden fijo: estéril y luego viable por nivel.
+    fn categories(&self) -> [u64; 6] {
+        let v = &self.viable_by_level;
+        [self.sterile, v[0], v[1], v[2], v[3], v[4]]
+    }
+
+    fn from_categories(counts: [u64; 6]) -> Self {
+        Self { sterile: counts[0], viable_by_level: [counts[1], counts[2], counts[3], counts[4], counts[5]], neutron_worlds: 0 }
+    }
+
+    /// Fracción viable seguida de la fracción de universos en cada nivel (los estériles cuentan en el nivel 0).
+    fn fractions(&self) -> [f64; 6] {
+        let total = self.total().max(1) as f64;
+        let v = &self.viable_by_level;
+        let viable: u64 = v.iter().sum();
+        [
+            viable as f64 / total,
+            (self.sterile + v[0]) as f64 / total,
+            v[1] as f64 / total,
+            v[2] as f64 / total,
+            v[3] as f64 / total,
+            v[4] as f64 / total,
+        ]
+    }
+
+    /// Remuestreo bootstrap: `total` extracciones con reemplazo, generadas como una multinomial
+    /// mediante binomiales condicionadas sucesivas.
+    fn resample(&self, rng: &mut impl Rng) -> Self {
+        let mut remaining = self.total();
+        let mut remaining_mass = remaining as f64;
+        let mut counts = [0u64; 6];
+        for (slot, &count) in counts.iter_mut().zip(self.categories().iter()) {
+            if remaining == 0 || remaining_mass <= 0.0 { break; }
+            let p = (count as f64 / remaining_mass).min(1.0);
+            *slot = Binomial::new(remaining, p).map_or(0, |b| b.sample(rng));
+            remaining -= *slot;
+            remaining_mass -= count as f64;
+        }
+        Self::from_categories(counts)
+    }
+}
+
+// --- LÓGICA DEL MODO BOOTSTRAP ---
+fn run_bootstrap_mode(input: &str, resamples: u32, confidence: f64) -> Result<(), Box<dyn Error>> {
+    let observed: MappingOutcomes = serde_json::from_str(&fs::read_to_string(input)?)?;
+    if observed.total() == 0 {
+        return Err("El recuento de resultados está vacío".into());
+    }
+    let mut rng = thread_rng();
+    progress!("{} universos, {} remuestreos, confianza {:.1}%", observed.total(), resamples, confidence * 100.0);
+
+    let mut samples: Vec<[f64; 6]> = (0..resamples).map(|_| observed.resample(&mut rng).fractions()).collect();
+    let labels = ["Viables", "Nivel 0", "Nivel 1", "Nivel 2", "Nivel 3", "Nivel 4"];
+    let estimates = observed.fractions();
+    let tail = (1.0 - confidence) / 2.0;
+    for (k, label) in labels.iter().enumerate() {
+        samples.sort_by(|a, b| a[k].partial_cmp(&b[k]).unwrap());
+        let quantile = |q: f64| samples[((q * resamples as f64) as usize).min(samples.len() - 1)][k];
+        println!(
+            "{:<8} {:>8.4}% [{:.4}%, {:.4}%]",
+            label, estimates[k] * 100.0, quantile(tail) * 100.0, quantile(1.0 - tail) * 100.0,
+        );
+    }
+    Ok(())
+}
+
+// --- LÓGICA DE LA FRACCIÓN VIABLE BAJO UNA MEDIDA ---
+/// Cuantil normal del intervalo de confianza del 95%.
+const Z_95: f64 = 1.959_964;
+
+/// Distribución a priori de un gen, restringida a la caja de muestreo del mapeo.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(tag = "distribution", rename_all = "kebab-case")]
+enum GenePrior {
+    Uniform { min: f64, max: f64 },
+    LogUniform { min: f64, max: f64 },
+}
+
+impl GenePrior {
+    fn bounds(self) -> (f64, f64) {
+        match self {
+            GenePrior::Uniform { min, max } | GenePrior::LogUniform { min, max } => (min, max),
+        }
+    }
+
+    fn density(self, x: f64) -> f64 {
+        let (min, max) = self.bounds();
+        if x < min || x > max {
+            return 0.0;
+        }
+        match self {
+            GenePrior::Uniform { .. } => 1.0 / (max - min),
+            GenePrior::LogUniform { .. } => 1.0 / (x * (max / min).ln()),
+        }
+    }
+}
+
+/// Medida a priori sobre el genoma: producto de las distribuciones de cada gen.
+struct PriorMeasure {
+    genes: [Option<GenePrior>; GENE_COUNT],
+}
+
+impl PriorMeasure {
+    /// Lee la medida y comprueba que cada distribución esté dentro del rango de muestreo de su gen
+    /// (`--ranges`, como en el mapeo): fuera de él no hay universos muestreados con los que estimarla.
+    fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let specified: HashMap<String, GenePrior> = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let mut genes = [None; GENE_COUNT];
+        for (name, prior) in specified {
+            let index = GENES.iter().position(|spec| spec.name == name)
+                .ok_or_else(|| format!("Gen desconocido en la medida a priori: '{}'", name))?;
+            let (min, max) = prior.bounds();
+            let spec = &RangeConfig::active().0[index];
+            if !(min > 0.0 && min < max) {
+                return Err(format!("La medida de '{}' necesita 0 < min < max (es [{}, {}])", name, min, max).into());
+            }
+            if min < spec.min || max > spec.max {
+                return Err(format!(
+                    "La medida de '{}' ([{}, {}]) sale del rango de muestreo del mapeo ([{}, {}])",
+                    name, min, max, spec.min, spec.max
+                ).into());
+            }
+            genes[index] = Some(prior);
+        }
+        Ok(Self { genes })
+    }
+
+    /// Peso de importancia `p(θ) / q(θ)` frente al muestreo uniforme del mapeo. Los genes sin
+    /// medida propia conservan la distribución del mapeo y no aportan factor.
+    fn importance_weight(&self, laws: &CosmicLaw) -> f64 {
+        self.genes.iter().zip(RangeConfig::active().0.iter()).zip(laws.genes())
+            .filter_map(|((prior, spec), x)| prior.map(|prior| prior.density(x) * (spec.max - spec.min)))
+            .product()
+    }
+}
+
+/// Fracción viable bajo la medida `p` a partir de un mapeo uniforme `q`:
+/// `P_p(viable) = P_q(viable) · E_q[p/q | viable]`. El primer factor sale del recuento completo del
+/// mapeo y el segundo de la muestra uniforme de universos viables del CSV. La incertidumbre combina
+/// ambas por el método delta (son estimaciones independientes): la binomial del recuento y el
+/// error estándar del peso medio.
+fn run_expected_viable_mode(outcomes_file: &str, input: &str, prior_file: &str, min_level: u8, universes: Option<f64>) -> Result<(), Box<dyn Error>> {
+    let outcomes: MappingOutcomes = serde_json::from_str(&fs::read_to_string(outcomes_file)?)?;
+    let prior = PriorMeasure::load(prior_file)?;
+    let total = outcomes.total();
+    if total == 0 {
+        return Err("El recuento de resultados está vacío".into());
+    }
+    let viable: u64 = outcomes.viable_by_level[min_level as usize..].iter().sum();
+    let box_fraction = viable as f64 / total as f64;
+    let box_se = (box_fraction * (1.0 - box_fraction) / total as f64).sqrt();
+
+    let weights: Vec<f64> = read_landscape(input)?.iter()
+        .filter(|row| row.fitness > 0.0 && row.level >= min_level)
+        .map(|row| prior.importance_weight(&row.laws))
+        .collect();
+    if weights.len() < 2 {
+        return Err(format!("El CSV tiene {} universos viables de nivel >= {}: hacen falta al menos 2", weights.len(), min_level).into());
+    }
+    let n = weights.len() as f64;
+    let mean_weight = weights.iter().sum::<f64>() / n;
+    let weight_variance = weights.iter().map(|w| (w - mean_weight).powi(2)).sum::<f64>() / (n - 1.0);
+    let weight_se = (weight_variance / n).sqrt();
+    // Tamaño efectivo de la muestra de Kish: cuántos universos aportan realmente información
+    let effective = weights.iter().sum::<f64>().powi(2) / weights.iter().map(|w| w * w).sum::<f64>();
+
+    let fraction = box_fraction * mean_weight;
+    let fraction_se = ((mean_weight * box_se).powi(2) + (box_fraction * weight_se).powi(2)).sqrt();
+
+    progress!("{} universos mapeados, {} viables de nivel >= {}; {} en la muestra del CSV", total, viable, min_level, weights.len());
+    println!("Fracción viable en la caja de muestreo: {:.6e} ± {:.2e}", box_fraction, box_se);
+    println!("Peso de importancia medio entre los viables: {:.4} ± {:.4} (tamaño efectivo {:.0} de {})", mean_weight, weight_se, effective, weights.len());
+    println!(
+        "Fracción viable bajo la medida: {:.6e} ± {:.2e} (95%: [{:.6e}, {:.6e}])",
+        fraction, fraction_se, (fraction - Z_95 * fraction_se).max(0.0), fraction + Z_95 * fraction_se
+    );
+    if let Some(universes) = universes {
+        println!("Universos viables esperados entre {:e}: {:.4e} ± {:.2e}", universes, universes * fraction, universes * fraction_se);
+    }
+    if effective < 0.1 * n {
+        progress!("Aviso: pocos universos dominan los pesos; la medida se concentra donde el mapeo apenas muestreó");
+    }
+    Ok(())
+}
+
+// --- LÓGICA DEL MODO DIMENSIÓN FRACTAL ---
+/// Una fila de `landscape_data.csv`.
+struct LandscapeRow {
     laws: CosmicLaw,
-    alpha: f64,
+    fitness: f64,
+    level: u8,
+}
+
+// --- LÓGICA DE LA CORRELACIÓN ENTRE GENES ---
+/// Rangos (1..=n) de `values`; los empates reciben el rango medio.
+fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let mean_rank = (start + end + 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            ranks[i] = mean_rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+/// Coeficiente de Pearson; NaN si alguna de las dos series es constante.
+fn pearson(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len() as f64;
+    let (mean_x, mean_y) = (x.iter().sum::<f64>() / n, y.iter().sum::<f64>() / n);
+    let (mut covariance, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+    for (a, b) in x.iter().zip(y) {
+        covariance += (a - mean_x) * (b - mean_y);
+        var_x += (a - mean_x).powi(2);
+        var_y += (b - mean_y).powi(2);
+    }
+    covariance / (var_x * var_y).sqrt()
+}
+
+fn run_gene_correlation_mode(input: &str, output: &str, method: CorrelationMethod, min_level: u8) -> Result<(), Box<dyn Error>> {
+    let rows: Vec<LandscapeRow> = read_landscape(input)?.into_iter().filter(|row| row.level >= min_level).collect();
+    if rows.len() < 3 {
+        return Err(format!("Se necesitan al menos 3 universos con nivel >= {} (hay {})", min_level, rows.len()).into());
+    }
+    progress!("Correlacionando {} universos con nivel >= {}", rows.len(), min_level);
+
+    // Una columna por gen, en espacio logarítmico (o en rangos para Spearman)
+    let logs: Vec<LogGenome> = rows.iter().map(|row| LogGenome::from_laws(&row.laws)).collect();
+    let columns: Vec<Vec<f64>> = (0..GENES.len())
+        .map(|i| {
+            let column: Vec<f64> = logs.iter().map(|genome| genome.0[i]).collect();
+            match method {
+                CorrelationMethod::Pearson => column,
+                CorrelationMethod::Spearman => ranks(&column),
+            }
+        })
+        .collect();
+    let matrix: Vec<Vec<f64>> = columns.iter()
+        .map(|x| columns.iter().map(|y| pearson(x, y)).collect())
+        .collect();
+
+    let mut wtr = csv::Writer::from_path(output)?;
+    write_run_metadata([output], "gene-correlation", &serde_json::json!({ "input": input, "output": output, "method": method, "min_level": min_level }), None, None)?;
+    wtr.write_record(std::iter::once("gene").chain(GENES.iter().map(|spec| spec.name)))?;
+    for (spec, row) in GENES.iter().zip(&matrix) {
+        wtr.write_record(std::iter::once(spec.name.to_string()).chain(row.iter().map(|&r| csv_float(r))))?;
+    }
+    wtr.flush()?;
+
+    // Pares más correlacionados (fuera de la diagonal) para una lectura rápida
+    let mut pairs: Vec<(usize, usize, f64)> = (0..GENES.len())
+        .flat_map(|i| ((i + 1)..GENES.len()).map(move |j| (i, j)))
+        .map(|(i, j)| (i, j, matrix[i][j]))
+        .filter(|(_, _, r)| r.is_finite())
+        .collect();
+    pairs.sort_by(|a, b| b.2.abs().total_cmp(&a.2.abs()));
+    progress!("Pares de genes más correlacionados:");
+    for (i, j, r) in pairs.iter().take(5) {
+        progress!("  {:<20} {:<20} {:>+.3}", GENES[*i].name, GENES[*j].name, r);
+    }
+    progress!("Matriz de correlación guardada en {}", output);
+    Ok(())
+}
+
+/// Constantes que resumen lo exótico de un universo en `relative_to_real`.
+const KEY_GENES: [Gene; 6] = [
+    Gene::G, Gene::E, Gene::AlphaS, Gene::MassElectron, Gene::MassUpQuark, Gene::MassDownQuark,
+];
+
+/// Constantes clave como múltiplos de `REAL_UNIVERSE`, p. ej. `G 3.20×, e 0.71×, ...`.
+fn relative_to_real(laws: &CosmicLaw) -> String {
+    KEY_GENES.iter()
+        .map(|&gene| format!("{} {:.2}×", gene.spec().name, laws.gene(gene) / REAL_UNIVERSE.gene(gene)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Posición de la columna `name` en la cabecera de un CSV con el genoma completo.
+fn csv_column(headers: &csv::StringRecord, name: &str, path: &str) -> Result<usize, String> {
+    headers.iter().position(|h| h == name)
+        .ok_or_else(|| format!("Falta la columna '{}' en {} (¿se generó con --compat-csv?)", name, path))
+}
+
+/// Columna de cada gen, en el orden de `GENES`. Los CSV anteriores al gen `omega_dm` no lo
+/// incluyen: `parse_genome` usa entonces el valor de nuestro universo.
+fn genome_columns(headers: &csv::StringRecord, path: &str) -> Result<Vec<Option<usize>>, String> {
+    Gene::ALL.iter()
+        .map(|&gene| match csv_column(headers, gene.spec().name, path) {
+            Ok(col) => Ok(Some(col)),
+            Err(_) if gene == Gene::OmegaDm => Ok(None),
+            Err(e) => Err(e),
+        })
+        .collect()
+}
+
+/// Reconstruye el genoma de una fila. Falla si alguna celda no es un número finito y positivo
+/// (celdas vacías, decimales con coma, `NaN`...).
+fn parse_genome(record: &csv::StringRecord, gene_cols: &[Option<usize>]) -> Result<CosmicLaw, String> {
+    let mut genes = REAL_UNIVERSE.genes();
+    for ((gene, col), spec) in genes.iter_mut().zip(gene_cols).zip(GENES.iter()) {
+        let Some(col) = col else { continue };
+        let cell = record.get(*col).unwrap_or("");
+        *gene = cell.trim().parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite() && *value > 0.0)
+            .ok_or_else(|| format!("valor no válido para '{}': {:?}", spec.name, cell))?;
+    }
+    Ok(CosmicLaw::from_genes(genes))
+}
+
+/// Filas mal formadas que un lector de CSV ha omitido.
+#[derive(Debug, Default)]
+struct SkippedRows {
+    count: u64,
+    first_error: Option<String>,
+}
+
+impl SkippedRows {
+    /// Cuenta una fila omitida; `line` es su índice entre las filas de datos (tras la cabecera).
+    fn record(&mut self, line: usize, error: impl std::fmt::Display) {
+        self.count += 1;
+        self.first_error.get_or_insert_with(|| format!("fila {}: {}", line + 1, error));
+    }
+
+    fn report(&self, path: &str) {
+        if self.count > 0 {
+            progress!(
+                "Aviso: se omitieron {} filas mal formadas de {} (la primera: {})",
+                self.count, path, self.first_error.as_deref().unwrap_or_default()
+            );
+        }
+    }
+}
+
+/// Lee un CSV del paisaje con el genoma completo. Las filas mal formadas se omiten y se cuentan
+/// en lugar de abortar: una fila corrupta no invalida un paisaje de millones de universos.
+fn read_landscape(path: &str) -> Result<Vec<LandscapeRow>, Box<dyn Error>> {
+    let (rows, skipped) = read_landscape_rows(path)?;
+    skipped.report(path);
+    Ok(rows)
+}
+
+/// Filas válidas del paisaje y recuento de las omitidas.
+fn read_landscape_rows(path: &str) -> Result<(Vec<LandscapeRow>, SkippedRows), Box<dyn Error>> {
+    let mut rdr = csv::Reader::from_path(path)?;
+    let headers = rdr.headers()?.clone();
+    let fitness_col = csv_column(&headers, "fitness", path)?;
+    let level_col = csv_column(&headers, "winning_gen", path)?;
+    let gene_cols = genome_columns(&headers, path)?;
+
+    let mut rows = Vec::new();
+    let mut skipped = SkippedRows::default();
+    for (line, record) in rdr.records().enumerate() {
+        let row = record.map_err(|e| e.to_string()).and_then(|record| {
+            let fitness = record.get(fitness_col).and_then(|cell| cell.trim().parse().ok())
+                .ok_or("fitness no válido")?;
+            let level = record.get(level_col).and_then(|cell| cell.trim().parse().ok())
+                .ok_or("winning_gen no válido")?;
+            Ok(LandscapeRow { laws: parse_genome(&record, &gene_cols)?, fitness, level })
+        });
+        match row {
+            Ok(row) => rows.push(row),
+            Err(e) => skipped.record(line, e),
+        }
+    }
+    Ok((rows, skipped))
+}
+
+// --- LÓGICA DE LA EVALUACIÓN DE UNA REJILLA EXTERNA ---
+/// Columnas que `eval-grid` añade a cada fila. Si el CSV de entrada ya trae alguna (p. ej. el
+/// `fitness` de un paisaje), se recalcula en lugar de duplicarla.
+const EVAL_GRID_COLUMNS: [&str; 8] = [
+    "fitness", "level", "classification",
+    "chemistry_score", "nuclear_score", "stellar_score", "heavy_elements_score", "reproduction_score",
+];
+
+fn run_eval_grid_mode(input: &str, output: &str, weights_file: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let weights = match weights_file {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    let mut rdr = csv::Reader::from_path(input)?;
+    let headers = rdr.headers()?.clone();
+    let gene_cols = genome_columns(&headers, input)?;
+    let kept_cols: Vec<usize> = (0..headers.len())
+        .filter(|&col| !EVAL_GRID_COLUMNS.contains(&&headers[col]))
+        .collect();
+
+    let mut rows: Vec<(csv::StringRecord, CosmicLaw)> = Vec::new();
+    let mut skipped = SkippedRows::default();
+    for (line, record) in rdr.records().enumerate() {
+        match record.map_err(|e| e.to_string()).and_then(|record| Ok((parse_genome(&record, &gene_cols)?, record))) {
+            Ok((laws, record)) => rows.push((record, laws)),
+            Err(e) => skipped.record(line, e),
+        }
+    }
+    skipped.report(input);
+    progress!("Evaluando {} genomas de {}...", rows.len(), input);
+
+    // La evaluación es una función pura del genoma: el orden de salida es el de entrada
+    let results: Vec<(Fitness, u8, EngineSummary)> = rows.par_iter()
+        .map(|(_, laws)| {
+            let FitnessReport { total: fitness, level, .. } = calculate_fitness(laws, &weights);
+            (fitness, level, PhysicsEngine::new(laws.clone(), PhysicalConstants::default()).summary())
+        })
+        .collect();
+
+    let mut wtr = csv::Writer::from_path(output)?;
+    write_run_metadata([output], "eval-grid", &serde_json::json!({ "input": input, "output": output }), None, Some(&weights))?;
+    wtr.write_record(kept_cols.iter().map(|&col| &headers[col]).chain(EVAL_GRID_COLUMNS))?;
+    let mut viable = 0;
+    for ((record, laws), (fitness, level, summary)) in rows.iter().zip(&results) {
+        if **fitness > 0.0 {
+            viable += 1;
+        }
+        let evaluated = [csv_float(**fitness), level.to_string(), classify_universe(laws, *fitness, *level).to_string()]
+            .into_iter()
+            .chain(summary.domains().into_iter().map(|(_, score)| csv_float(*score)));
+        wtr.write_record(kept_cols.iter().map(|&col| record[col].to_string()).chain(evaluated))?;
+    }
+    wtr.flush()?;
+    progress!("{} de {} genomas son viables", viable, rows.len());
+    progress!("Resultados guardados en {}", output);
+    Ok(())
+}
+
+/// Pendiente por mínimos cuadrados de `y` frente a `x`.
+fn least_squares_slope(points: &[(f64, f64)]) -> f64 {
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let variance: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    covariance / variance
+}
+
+/// Conteo de cajas: para cada escala con `2^k` cajas por eje (en escala logarítmica dentro de
+/// los límites de cada eje) se cuentan las cajas ocupadas `N(k)`; la dimensión es la pendiente
+/// de `ln N` frente a `ln 2^k`. Con una muestra finita, `N` se satura cerca del número de
+/// puntos en las escalas finas, así que conviene no pedir más cajas que puntos.
+fn run_fractal_dimension_mode(input: &str, x: PhaseAxis, y: PhaseAxis, min_level: u8, max_exponent: u32) -> Result<(), Box<dyn Error>> {
+    let normalized = |axis: PhaseAxis, laws: &CosmicLaw| {
+        let (lo, hi) = axis.bounds();
+        (axis.value(laws).ln() - lo.ln()) / (hi.ln() - lo.ln())
+    };
+    let points: Vec<(f64, f64)> = read_landscape(input)?.iter()
+        .filter(|row| row.fitness > 0.0 && row.level >= min_level)
+        .map(|row| (normalized(x, &row.laws), normalized(y, &row.laws)))
+        .filter(|(u, v)| (0.0..=1.0).contains(u) && (0.0..=1.0).contains(v))
+        .collect();
+    if points.is_empty() {
+        return Err(format!("No hay universos de nivel >= {} dentro de los límites de la proyección", min_level).into());
+    }
+    progress!("{} universos en la proyección {} × {}", points.len(), x.name(), y.name());
+
+    let mut log_counts = Vec::new();
+    progress!("{:>8} {:>12}", "cajas/eje", "ocupadas");
+    for k in 1..=max_exponent {
+        let bins = 1usize << k;
+        let cell = |t: f64| ((t * bins as f64) as usize).min(bins - 1);
+        let occupied: std::collections::HashSet<(usize, usize)> = points.iter().map(|&(u, v)| (cell(u), cell(v))).collect();
+        progress!("{:>8} {:>12}", bins, occupied.len());
+        log_counts.push(((bins as f64).ln(), (occupied.len() as f64).ln()));
+    }
+    println!("Dimensión de conteo de cajas: {:.3}", least_squares_slope(&log_counts));
+    Ok(())
+}
+
+// --- REPRODUCCIÓN DE UN UNIVERSO DEL MAPEO ---
+/// Semilla del universo `index` de un mapeo uniforme: finalizador de SplitMix64 sobre la semilla
+/// base desplazada por el índice, para que índices consecutivos den flujos independientes.
+fn universe_seed(base_seed: u64, index: u64) -> u64 {
+    let mut z = base_seed.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// --- GENERADORES DETERMINISTAS POR TRABAJADOR ---
+/// Reparto de generadores aleatorios entre trabajadores: el flujo de cada uno se deriva de la
+/// semilla base y su índice, así que el resultado sólo depende de la semilla y del número de
+/// trabajadores, no del orden en que los hilos de rayon los ejecuten.
+#[derive(Debug, Clone, Copy)]
+struct RngPool {
+    base_seed: u64,
+}
+
+impl RngPool {
+    fn new(base_seed: u64) -> Self {
+        RngPool { base_seed }
+    }
+
+    /// Generador propio del trabajador `worker`, independiente de los demás. Es el mismo ChaCha12
+    /// (y el mismo flujo) que `StdRng`, pero serializable para los puntos de control del AG.
+    fn stream(&self, worker: u64) -> ChaCha12Rng {
+        ChaCha12Rng::seed_from_u64(universe_seed(self.base_seed, worker))
+    }
+}
+
+fn run_replay_mode(seed: u64, index: u64, weights_file: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let weights = match weights_file {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    let laws = CosmicLaw::random(&mut StdRng::seed_from_u64(universe_seed(seed, index)));
+    let FitnessReport { total: fitness, level, .. } = calculate_fitness(&laws, &weights);
+    progress!("Universo #{} (semilla base {}): fitness {:.6}, nivel {} ({})", index, seed, *fitness, level, classify_universe(&laws, fitness, level));
+    println!("{}", serde_json::to_string_pretty(&laws)?);
+    Ok(())
+}
+
+// --- ESQUEMA DEL CSV DEL PAISAJE ---
+/// Columnas de `landscape_data.csv`: el genoma completo, o el formato antiguo de seis masas
+/// (`--compat-csv`) para scripts que indexan las columnas por posición.
+#[derive(Debug, Clone, Copy)]
+enum LandscapeSchema {
+    Full,
+    Compat,
+}
+
+impl LandscapeSchema {
+    const COMPAT_GENES: [Gene; 6] = [
+        Gene::MassUpQuark, Gene::MassDownQuark, Gene::MassStrangeQuark,
+        Gene::MassCharmQuark, Gene::MassBottomQuark, Gene::MassTopQuark,
+    ];
+
+    fn genes(self) -> &'static [Gene] {
+        match self {
+            LandscapeSchema::Full => &Gene::ALL,
+            LandscapeSchema::Compat => &Self::COMPAT_GENES,
+        }
+    }
+
+    fn header(self) -> Vec<&'static str> {
+        let mut header = vec!["fitness", "winning_gen"];
+        if let LandscapeSchema::Full = self {
+            header.push("index");
+        }
+        header.into_iter()
+            .chain(self.genes().iter().map(|gene| gene.spec().name))
+            .collect()
+    }
+
+    /// `index` es la posición del universo en el mapeo (para `replay`); el formato antiguo no la incluye.
+    fn record(self, index: u64, laws: &CosmicLaw, fitness: Fitness, winning_gen: u8) -> Vec<String> {
+        let mut record = vec![csv_float(*fitness), winning_gen.to_string()];
+        if let LandscapeSchema::Full = self {
+            record.push(index.to_string());
+        }
+        record.into_iter()
+            .chain(self.genes().iter().map(|&gene| csv_float(laws.gene(gene))))
+            .collect()
+    }
+}
+
+/// Columnas de `--detailed`, en el orden de `EngineSummary::domains`. Son las mismas que añade
+/// `eval-grid`, que las recalcula si el CSV de entrada ya las trae.
+const DOMAIN_SCORE_COLUMNS: [&str; 5] = [
+    "chemistry_score", "nuclear_score", "stellar_score", "heavy_elements_score", "reproduction_score",
+];
+
+/// Sub-puntuaciones sin ponderar de cada dominio, en el orden de `DOMAIN_SCORE_COLUMNS`.
+fn domain_scores(laws: &CosmicLaw) -> [f64; 5] {
+    PhysicsEngine::new(laws.clone(), PhysicalConstants::default()).summary().domains().map(|(_, score)| *score)
+}
+
+// --- FORMATO DE SALIDA DEL PAISAJE ---
+/// Formato de la muestra del mapeo (`--format`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum LandscapeFormat {
+    Csv,
+    Jsonl,
+}
+
+impl LandscapeFormat {
+    fn file_name(self) -> &'static str {
+        match self {
+            LandscapeFormat::Csv => "landscape_data.csv",
+            LandscapeFormat::Jsonl => "landscape_data.jsonl",
+        }
+    }
+
+    /// Crea el archivo de salida. El escritor se elige aquí una sola vez, no en cada fila.
+    /// Con `detailed`, cada fila lleva además las sub-puntuaciones de `domain_scores`.
+    fn create(self, schema: LandscapeSchema, detailed: bool) -> Result<Box<dyn LandscapeWriter>, Box<dyn Error>> {
+        Ok(match self {
+            LandscapeFormat::Csv => {
+                let mut wtr = csv::Writer::from_path(self.file_name())?;
+                let mut header = schema.header();
+                if detailed {
+                    header.extend(DOMAIN_SCORE_COLUMNS);
+                }
+                wtr.write_record(header)?;
+                Box::new(CsvLandscapeWriter { wtr, schema, detailed })
+            }
+            LandscapeFormat::Jsonl => Box::new(JsonLinesLandscapeWriter {
+                out: std::io::BufWriter::new(fs::File::create(self.file_name())?),
+                detailed,
+            }),
+        })
+    }
+}
+
+/// Destino de las filas de la muestra del mapeo, en cualquiera de sus formatos.
+trait LandscapeWriter: Send {
+    fn write(&mut self, universe: &MappedUniverse) -> std::io::Result<()>;
+    fn flush(&mut self) -> std::io::Result<()>;
+}
+
+struct CsvLandscapeWriter {
+    wtr: csv::Writer<fs::File>,
+    schema: LandscapeSchema,
+    detailed: bool,
+}
+
+impl LandscapeWriter for CsvLandscapeWriter {
+    fn write(&mut self, (index, laws, fitness, winning_gen): &MappedUniverse) -> std::io::Result<()> {
+        let mut record = self.schema.record(*index, laws, *fitness, *winning_gen);
+        if self.detailed {
+            record.extend(domain_scores(laws).map(csv_float));
+        }
+        Ok(self.wtr.write_record(record)?)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.wtr.flush()
+    }
+}
+
+/// Una línea de `landscape_data.jsonl`. Los genes se escriben como números JSON, así que su valor
+/// se recupera exactamente al leerlos (`float_roundtrip`); un valor no finito se escribe como `null`.
+#[derive(Serialize)]
+struct LandscapeLine<'a> {
+    index: u64,
+    #[serde(flatten)]
+    laws: &'a CosmicLaw,
+    fitness: f64,
+    winning_gen: u8,
+    universe_type: &'static str,
+    /// Vacío salvo con `--detailed`.
+    #[serde(flatten)]
+    scores: BTreeMap<&'static str, f64>,
+}
+
+struct JsonLinesLandscapeWriter {
+    out: std::io::BufWriter<fs::File>,
+    detailed: bool,
+}
+
+impl LandscapeWriter for JsonLinesLandscapeWriter {
+    fn write(&mut self, (index, laws, fitness, winning_gen): &MappedUniverse) -> std::io::Result<()> {
+        let line = LandscapeLine {
+            index: *index,
+            laws,
+            fitness: **fitness,
+            winning_gen: *winning_gen,
+            universe_type: classify_universe(laws, *fitness, *winning_gen),
+            scores: if self.detailed {
+                DOMAIN_SCORE_COLUMNS.into_iter().zip(domain_scores(laws)).collect()
+            } else {
+                BTreeMap::new()
+            },
+        };
+        serde_json::to_writer(&mut self.out, &line)?;
+        self.out.write_all(b"\n")
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+}
+
+// --- MUESTREO POR RESERVORIO ---
+/// Muestra aleatoria uniforme de tamaño fijo sobre un flujo de longitud desconocida (Algoritmo R):
+/// el elemento `n`-ésimo entra con probabilidad `capacity / n`, reemplazando a uno al azar.
+struct Reservoir<T> {
+    capacity: usize,
+    seen: u64,
+    items: Vec<T>,
+}
+
+impl<T> Reservoir<T> {
+    fn new(capacity: usize) -> Self {
+        Self { capacity, seen: 0, items: Vec::with_capacity(capacity.min(1 << 20)) }
+    }
+
+    fn offer(&mut self, item: T, rng: &mut impl Rng) {
+        self.seen += 1;
+        if self.items.len() < self.capacity {
+            self.items.push(item);
+        } else {
+            let j = rng.gen_range(0..self.seen);
+            if j < self.capacity as u64 {
+                self.items[j as usize] = item;
+            }
+        }
+    }
+}
+
+/// Muestra aleatoria uniforme de tamaño fijo que no depende del orden de llegada (muestreo
+/// bottom-k): cada elemento recibe una prioridad pseudoaleatoria derivada de su índice y se
+/// conservan los `capacity` de menor prioridad. Dos muestras parciales se combinan sin sesgo.
+struct PrioritySample<T> {
+    capacity: usize,
+    key_seed: u64,
+    seen: u64,
+    heap: BinaryHeap<Prioritized<T>>,
+}
+
+/// Elemento de una `PrioritySample`, ordenado por (prioridad, índice).
+struct Prioritized<T> {
+    key: u64,
+    index: u64,
+    item: T,
+}
+
+impl<T> PartialEq for Prioritized<T> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.key, self.index) == (other.key, other.index)
+    }
+}
+
+impl<T> Eq for Prioritized<T> {}
+
+impl<T> PartialOrd for Prioritized<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Prioritized<T> {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        (self.key, self.index).cmp(&(other.key, other.index))
+    }
+}
+
+impl<T> PrioritySample<T> {
+    /// Sal de las prioridades: las separa del flujo de `universe_seed` que genera cada universo.
+    const KEY_SALT: u64 = 0x5851_F42D_4C95_7F2D;
+
+    fn new(capacity: usize, base_seed: u64) -> Self {
+        Self { capacity, key_seed: base_seed ^ Self::KEY_SALT, seen: 0, heap: BinaryHeap::new() }
+    }
+
+    fn offer(&mut self, index: u64, item: T) {
+        self.seen += 1;
+        self.insert(Prioritized { key: universe_seed(self.key_seed, index), index, item });
+    }
+
+    fn insert(&mut self, entry: Prioritized<T>) {
+        if self.heap.len() < self.capacity {
+            self.heap.push(entry);
+        } else if self.heap.peek().is_some_and(|worst| entry < *worst) {
+            self.heap.pop();
+            self.heap.push(entry);
+        }
+    }
+
+    fn merge(mut self, other: Self) -> Self {
+        self.seen += other.seen;
+        for entry in other.heap {
+            self.insert(entry);
+        }
+        self
+    }
+
+    /// Elementos de la muestra ordenados por índice.
+    fn into_items(self) -> Vec<T> {
+        let mut entries = self.heap.into_vec();
+        entries.sort_by_key(|entry| entry.index);
+        entries.into_iter().map(|entry| entry.item).collect()
+    }
+}
+
+/// Muestra de universos del mapeo: por reservorio (flujo secuencial) o por prioridad (`--deterministic`).
+enum UniverseSample<T> {
+    Reservoir(Reservoir<T>),
+    Priority(PrioritySample<T>),
+}
+
+impl<T> UniverseSample<T> {
+    fn offer(&mut self, index: u64, item: T, rng: &mut impl Rng) {
+        match self {
+            UniverseSample::Reservoir(reservoir) => reservoir.offer(item, rng),
+            UniverseSample::Priority(sample) => sample.offer(index, item),
+        }
+    }
+
+    /// Elementos elegibles ofrecidos a la muestra.
+    fn seen(&self) -> u64 {
+        match self {
+            UniverseSample::Reservoir(reservoir) => reservoir.seen,
+            UniverseSample::Priority(sample) => sample.seen,
+        }
+    }
+
+    fn into_items(self) -> Vec<T> {
+        match self {
+            UniverseSample::Reservoir(reservoir) => reservoir.items,
+            UniverseSample::Priority(sample) => sample.into_items(),
+        }
+    }
+}
+
+// --- MUESTREO POR HIPERCUBO LATINO ---
+/// Genera genomas por bloques de hipercubo latino: dentro de cada bloque de `n` universos,
+/// el rango de cada gen se divide en `n` estratos y cada estrato se visita exactamente una vez.
+/// Los bloques acotan la memoria (una permutación de `LHS_BLOCK` índices por gen) en mapeos enormes.
+struct LatinHypercube {
+    remaining: u64,
+    permutations: Vec<Vec<u32>>,
+    position: usize,
+}
+
+impl LatinHypercube {
+    const LHS_BLOCK: u64 = 100_000;
+
+    fn new(total: u64) -> Self {
+        Self { remaining: total, permutations: vec![Vec::new(); GENES.len()], position: 0 }
+    }
+
+    fn refill(&mut self, rng: &mut impl Rng) {
+        let block = self.remaining.clamp(1, Self::LHS_BLOCK) as u32;
+        self.remaining = self.remaining.saturating_sub(block as u64);
+        for permutation in self.permutations.iter_mut() {
+            *permutation = (0..block).collect();
+            permutation.shuffle(rng);
+        }
+        self.position = 0;
+    }
+
+    fn next_genome(&mut self, rng: &mut impl Rng) -> CosmicLaw {
+        if self.position >= self.permutations[0].len() {
+            self.refill(rng);
+        }
+        let block = self.permutations[0].len() as f64;
+        let position = self.position;
+        self.position += 1;
+        let ranges = RangeConfig::active();
+        let genes = std::array::from_fn(|g| {
+            let spec = &ranges.0[g];
+            let u = (self.permutations[g][position] as f64 + rng.gen::<f64>()) / block;
+            spec.min + u * (spec.max - spec.min)
+        });
+        CosmicLaw::from_genes(genes)
+    }
+}
+
+// --- DIAGRAMA DE FASES ---
+/// Acoplamientos que pueden usarse como ejes del diagrama de fases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum PhaseAxis {
+    /// Constante de acoplamiento fuerte.
+    AlphaS,
+    /// Constante de estructura fina (derivada de `e`).
+    Alpha,
+    /// Constante de acoplamiento débil.
+    AlphaW,
+    /// Constante gravitatoria.
+    G,
+}
+
+impl PhaseAxis {
+    fn name(self) -> &'static str {
+        match self {
+            PhaseAxis::AlphaS => "alpha_s",
+            PhaseAxis::Alpha => "alpha",
+            PhaseAxis::AlphaW => "alpha_w",
+            PhaseAxis::G => "G",
+        }
+    }
+
+    fn value(self, laws: &CosmicLaw) -> f64 {
+        match self {
+            PhaseAxis::AlphaS => laws.alpha_s,
+            PhaseAxis::Alpha => PhysicalConstants::default().fine_structure(laws.e),
+            PhaseAxis::AlphaW => laws.alpha_w,
+            PhaseAxis::G => laws.G,
+        }
+    }
+
+    /// Límites del eje, iguales a los rangos de muestreo del modo mapeo.
+    fn bounds(self) -> (f64, f64) {
+        let ranges = RangeConfig::active();
+        let range = |gene: Gene| (ranges.spec(gene).min, ranges.spec(gene).max);
+        match self {
+            PhaseAxis::AlphaS => range(Gene::AlphaS),
+            PhaseAxis::Alpha => {
+                let constants = PhysicalConstants::default();
+                let (min, max) = range(Gene::E);
+                (constants.fine_structure(min), constants.fine_structure(max))
+            }
+            PhaseAxis::AlphaW => range(Gene::AlphaW),
+            PhaseAxis::G => range(Gene::G),
+        }
+    }
 }
 
-// --- IMPLEMENTACIÓN DEL MOTOR DE FÍSICA (v18.0 - CON GRADIENTES SUAVES) ---
-// --- IMPLEMENTACIÓN DEL MOTOR DE FÍSICA (v18.1 - COMPLETO) ---
-impl PhysicsEngine {
-    fn new(laws: CosmicLaw) -> Self {
-        let alpha = laws.e.powi(2) / (4.0 * PI * EPSILON_0 * H_BAR * C);
-        Self { laws, alpha }
-    }
+/// Rejilla 2D (en escala logarítmica) que acumula un histograma de niveles de complejidad por celda.
+struct PhaseDiagram {
+    x: PhaseAxis,
+    y: PhaseAxis,
+    bins: usize,
+    level_counts: Vec<[u64; 5]>,
+    viable_counts: Vec<u64>,
+}
 
-    fn chandrasekhar_mass(&self) -> f64 {
-        let m_proton = self.laws.mass_up_quark * 2.0 + self.laws.mass_down_quark;
-        if m_proton <= 0.0 || self.laws.G <= 0.0 { return 0.0; }
-        let mu_e = 2.0;
-        (H_BAR * C / self.laws.G).powf(1.5) / (m_proton * mu_e).powi(2)
+impl PhaseDiagram {
+    fn new(x: PhaseAxis, y: PhaseAxis, bins: usize) -> Self {
+        let bins = bins.max(1);
+        Self { x, y, bins, level_counts: vec![[0; 5]; bins * bins], viable_counts: vec![0; bins * bins] }
     }
 
-    fn calculate_stellar_viability(&self) -> f64 {
-        let t_core = 1.5e7;
-        let thermal_energy = K_B * t_core;
-        let m_proton = 2.0 * self.laws.mass_up_quark + self.laws.mass_down_quark;
-        if m_proton <= 0.0 { return 0.0; }
-        let m_reduced = m_proton / 2.0;
+    fn bin_index(&self, axis: PhaseAxis, value: f64) -> Option<usize> {
+        let (lo, hi) = axis.bounds();
+        let t = (value.log10() - lo.log10()) / (hi.log10() - lo.log10());
+        if !(0.0..=1.0).contains(&t) { return None; }
+        Some(((t * self.bins as f64) as usize).min(self.bins - 1))
+    }
 
-        let gamow_energy = 2.0 * m_reduced * C.powi(2) * (PI * self.alpha).powi(2);
-        if thermal_energy <= 0.0 || gamow_energy < 0.0 { return 0.0; }
-        let tunnel_exponent = - (gamow_energy / thermal_energy).sqrt();
-        let fusion_rate = tunnel_exponent.exp();
-        
-        if fusion_rate < 1e-30 { return 0.0; }
-        let log_rate = fusion_rate.ln();
-        
-        1.0 / (1.0 + ((-log_rate - 50.0) / 10.0).exp())
+    fn bin_center(&self, axis: PhaseAxis, index: usize) -> f64 {
+        let (lo, hi) = axis.bounds();
+        let t = (index as f64 + 0.5) / self.bins as f64;
+        10f64.powf(lo.log10() + t * (hi.log10() - lo.log10()))
     }
-    
-    fn calculate_black_hole_potential(&self) -> f64 {
-        let m_ch = self.chandrasekhar_mass();
-        if m_ch.is_nan() || m_ch.is_infinite() || m_ch <= 0.0 { return 0.0; }
-        let target_log_mass = (8.0 * M_SOLAR).log10();
-        let current_log_mass = m_ch.log10();
-        
-        let exponent = -((current_log_mass - target_log_mass).powi(2)) / (2.0 * 1.0_f64.powi(2));
-        exponent.exp()
+
+    fn record(&mut self, laws: &CosmicLaw, fitness: Fitness, level: u8) {
+        let (Some(ix), Some(iy)) = (self.bin_index(self.x, self.x.value(laws)), self.bin_index(self.y, self.y.value(laws))) else {
+            return;
+        };
+        let cell = iy * self.bins + ix;
+        self.level_counts[cell][(level as usize).min(4)] += 1;
+        if *fitness > 0.0 {
+            self.viable_counts[cell] += 1;
+        }
     }
-    
-    fn nuclear_stability_score(&self) -> f64 {
-        let m_proton = 2.0 * self.laws.mass_up_quark + self.laws.mass_down_quark;
-        if m_proton <= 0.0 { return 0.0; }
-
-        let binding_energy_deuterium = self.laws.alpha_s * m_proton * C.powi(2) * 0.0023;
-        let target_binding_joules = 2.22 * 1.602e-13;
-        if target_binding_joules <= 0.0 { return 0.0; }
-        let relative_error = (binding_energy_deuterium - target_binding_joules).abs() / target_binding_joules;
-        
-        (-relative_error.powi(2) / 0.5).exp()
+
+    fn merge(&mut self, other: &PhaseDiagram) {
+        for (cell, other_cell) in self.level_counts.iter_mut().zip(&other.level_counts) {
+            for (count, other_count) in cell.iter_mut().zip(other_cell) {
+                *count += other_count;
+            }
+        }
+        for (count, other_count) in self.viable_counts.iter_mut().zip(&other.viable_counts) {
+            *count += other_count;
+        }
     }
 
-    // FUNCIÓN AÑADIDA QUE FALTABA
-    fn heavy_elements_viability(&self) -> f64 {
-        let alpha_s_optimal = 0.118;
-        let alpha_s_error = (self.laws.alpha_s - alpha_s_optimal).abs() / alpha_s_optimal;
-        
-        if alpha_s_error < 0.5 {
-            1.0 - alpha_s_error
-        } else {
-            0.0
+    fn write(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let mut wtr = csv::Writer::from_path(path)?;
+        wtr.write_record([self.x.name(), self.y.name(), "universes", "viable_fraction", "modal_level", "modal_type"])?;
+        for iy in 0..self.bins {
+            for ix in 0..self.bins {
+                let cell = iy * self.bins + ix;
+                let counts = &self.level_counts[cell];
+                let total: u64 = counts.iter().sum();
+                if total == 0 { continue; }
+                // La moda favorece el nivel más bajo en caso de empate
+                let modal_level = (0..5).rev().max_by_key(|&level| counts[level]).unwrap_or(0) as u8;
+                wtr.write_record(&[
+                    csv_float(self.bin_center(self.x, ix)),
+                    csv_float(self.bin_center(self.y, iy)),
+                    total.to_string(),
+                    csv_float(self.viable_counts[cell] as f64 / total as f64),
+                    modal_level.to_string(),
+                    analyze_universe_type(modal_level).to_string(),
+                ])?;
+            }
         }
+        wtr.flush()?;
+        Ok(())
     }
 }
 
-fn calculate_fitness(laws: &CosmicLaw) -> (f64, u8) {
-    let engine = PhysicsEngine::new(laws.clone());
-
-    let mass_proton = 2.0 * laws.mass_up_quark + laws.mass_down_quark;
-    let mass_neutron = laws.mass_up_quark + 2.0 * laws.mass_down_quark;
-    
-    // Verificación de viabilidad básica
-    if mass_proton >= mass_neutron || mass_proton + laws.mass_electron <= mass_neutron {
-        return (0.0, 0);
+fn run_evolutionary_mode(args: &EvolveArgs) -> Result<(), Box<dyn Error>> {
+    // --- 1. SETUP ---
+    OperatorRates::from_args(args).validate()?;
+    let seed_population = args.seed_population.as_deref().map(load_seed_population).transpose()?;
+    let checkpoint = args.resume.as_deref().map(EvolutionCheckpoint::load).transpose()?;
+    let adam_genome: CosmicLaw = match (&args.seed, &seed_population, &checkpoint) {
+        (Some(path), _, _) => CosmicLaw::load(path)?,
+        (None, Some(population), _) => population[0].clone(),
+        (None, None, Some(checkpoint)) => checkpoint.adam.clone(),
+        (None, None, None) => unreachable!("clap exige --seed, --seed-population o --resume"),
+    };
+    // Al reanudar, el generador sale del punto de control y la semilla base no interviene
+    let base_seed = args.rng_seed.unwrap_or_else(|| thread_rng().gen());
+    if checkpoint.is_none() {
+        progress!("Semilla del generador: {}", base_seed);
     }
+    let pool = RngPool::new(base_seed);
+    let weights = match &args.weights {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
 
-    let mut fitness = 0.0;
-    let mut complexity_level = 0;
-
-    // NIVEL 1: Química Básica (0.0-0.2)
-    let stability_margin = mass_neutron - mass_proton;
-    let atomic_fitness = (stability_margin / mass_proton).min(0.1);
-    
-    // Bonus por enlace electromagnético estable
-    let bohr_radius = 4.0 * PI * EPSILON_0 * H_BAR.powi(2) / (laws.mass_electron * laws.e.powi(2));
-    let em_stability = if bohr_radius > 0.0 && bohr_radius < 1e-9 { 0.1 } else { 0.0 };
-    
-    fitness += atomic_fitness + em_stability;
-    
-    if fitness >= 0.15 {
-        complexity_level = 1; // Universo con átomos
-        
-        // NIVEL 2: Física Nuclear y Estelar (0.0-0.35)
-        let nuclear_score = engine.nuclear_stability_score();
-        let stellar_score = engine.calculate_stellar_viability();
-        let nuclear_fitness = 0.15 * nuclear_score + 0.2 * stellar_score;
-        
-        fitness += nuclear_fitness;
-        
-        if fitness >= 0.4 {
-            complexity_level = 2; // Universo con estrellas
-            
-            // NIVEL 3: Elementos Pesados y Complejidad (0.0-0.25)
-            let heavy_elements = engine.heavy_elements_viability();
-            let complexity_fitness = 0.25 * heavy_elements;
-            
-            fitness += complexity_fitness;
-            
-            if fitness >= 0.6 {
-                complexity_level = 3; // Universo con química compleja
-                
-                // NIVEL 4: Potencial Reproductivo (0.0-0.2)
-                let reproductive_fitness = 0.2 * engine.calculate_black_hole_potential();
-                fitness += reproductive_fitness;
-                
-                if fitness >= 0.75 {
-                    complexity_level = 4; // Universo auto-reproductivo
-                }
+    // El objetivo "target-distance" sustituye la física por una distancia conocida,
+    // de modo que el óptimo es exactamente el genoma objetivo.
+    let target_genome = args.target.as_deref().map(CosmicLaw::load).transpose()?;
+    let reference = PhysicsEngine::from_real_universe();
+    let evaluate = |laws: &CosmicLaw| -> f64 {
+        if let Some(domain) = args.objective.domain() {
+            let summary = PhysicsEngine::new(laws.clone(), PhysicalConstants::default()).summary();
+            return *summary.domains()[domain].1;
+        }
+        match (args.objective, &target_genome) {
+            (Objective::TargetDistance, Some(target)) => -laws.log_distance(target),
+            (Objective::Robustness, _) => {
+                // Las perturbaciones se siembran con la huella del genoma: un mismo genoma recibe
+                // siempre la misma puntuación y la selección no persigue ruido de muestreo.
+                let engine = PhysicsEngine::new(laws.clone(), PhysicalConstants::default());
+                let mut perturbation_rng = StdRng::seed_from_u64(laws.fingerprint());
+                engine.robustness_score(&weights, args.robustness_samples, &mut perturbation_rng)
             }
+            (Objective::MinimizeResidual, _) => {
+                let engine = PhysicsEngine::new(laws.clone(), PhysicalConstants::default());
+                -engine.observable_residuals(&reference).iter().map(|(_, r)| r).sum::<f64>()
+            }
+            _ => *calculate_fitness(laws, &weights).total,
         }
-    }
+    };
 
-    (fitness, complexity_level)
-}
+    let manifest = match &args.write_manifest {
+        Some(path) => {
+            let seed_arguments = args.rng_seed.is_none().then(|| ["--rng-seed".to_string(), base_seed.to_string()]);
+            let outputs: Vec<&str> = if args.restarts.is_some() {
+                vec![RESTARTS_FILE, BEST_RESTART_FILE]
+            } else {
+                std::iter::once("evolution_data.csv")
+                    .chain([args.output.as_str()])
+                    .chain(args.trajectory.as_deref())
+                    .chain(args.newick.as_deref())
+                    .chain(args.emit_seed_on_improvement.then_some(BEST_SO_FAR_FILE))
+                    .collect()
+            };
+            let manifest = ExperimentManifest::new("evolve", seed_arguments.into_iter().flatten(), args, base_seed, &weights, outputs)?;
+            write_json_atomically(path, &manifest)?;
+            Some((path, manifest))
+        }
+        None => None,
+    };
+
+    if args.restarts.is_some() {
+        run_restarts(args, pool, &adam_genome, seed_population.as_deref(), target_genome.as_ref(), &evaluate, &weights)?;
+    } else {
+        let outputs = std::iter::once("evolution_data.csv").chain(args.trajectory.as_deref()).chain(args.newick.as_deref());
+        write_run_metadata(outputs, "evolve", args, checkpoint.is_none().then_some(base_seed), Some(&weights))?;
 
-// Función auxiliar para análisis del paisaje
-fn analyze_universe_type(fitness: f64, level: u8) -> &'static str {
-    match level {
-        0 => "Estéril",
-        1 => "Químico",
-        2 => "Estelar", 
-        3 => "Complejo",
-        4 => "Reproductivo",
-        _ => "Desconocido"
+        let mut rng = pool.stream(0);
+        if args.pso {
+            run_particle_swarm(args, &adam_genome, &evaluate, &mut rng)?;
+        } else {
+            let start = match (checkpoint, seed_population.as_deref()) {
+                (Some(checkpoint), _) => GaStart::Resumed(Box::new(checkpoint)),
+                (None, Some(seeds)) => GaStart::Seeded(seeds),
+                (None, None) => GaStart::Adam,
+            };
+            run_genetic_algorithm(args, &adam_genome, start, target_genome.as_ref(), &evaluate, &mut rng, false)?;
+        }
     }
+    if let Some((path, mut manifest)) = manifest {
+        manifest.record_digests()?;
+        write_json_atomically(path, &manifest)?;
+    }
+    Ok(())
 }
 
-// --- DEFINICIÓN DE LA INTERFAZ DE LÍNEA DE COMANDOS (CLI) ---
-#[derive(Parser)]
-#[command(author, version, about = "Simulador Cosmológico 'El Armónico 137'", long_about = None)]
-struct Cli {
-    #[command(subcommand)]
-    command: Commands,
+// --- PUNTOS DE CONTROL DEL AG ---
+/// Población inicial del AG.
+enum GaStart<'a> {
+    /// Mutantes del genoma de Adán.
+    Adam,
+    /// Población sembrada con `--seed-population`.
+    Seeded(&'a [CosmicLaw]),
+    /// Estado guardado en un punto de control (`--resume`).
+    Resumed(Box<EvolutionCheckpoint>),
 }
 
-#[derive(Subcommand)]
-enum Commands {
-    /// Modo Mapeo: Simula N universos aleatorios para encontrar candidatos viables.
-    Map {
-        #[arg(short, long, default_value_t = 5_000_000)]
-        universes: u64,
-    },
-    /// Modo Evolutivo: Evoluciona una población a partir de una semilla.
-    Evolve {
-        #[arg(short, long)]
-        seed: String,
-        #[arg(short, long, default_value_t = 500)]
-        generations: u32,
-    },
+/// Todo lo que el bucle generacional arrastra de una generación a la siguiente, guardado tras
+/// crear la población de `generation` y antes de evaluarla. Reanudar desde aquí reproduce bit a
+/// bit la ejecución ininterrumpida con los mismos argumentos.
+#[derive(Serialize, Deserialize)]
+struct EvolutionCheckpoint {
+    /// Generación por la que continúa la evolución.
+    generation: u64,
+    adam: CosmicLaw,
+    /// Población en espacio log-genético, tal como la evoluciona el AG: reconstruirla desde
+    /// unidades físicas alteraría los últimos bits de los genes y con ellos el fitness de la élite.
+    population: Vec<LogGenome>,
+    /// Individuos al inicio de `population` que son élite heredada (el campeón anterior).
+    elite_count: usize,
+    best_so_far: f64,
+    streaks: ChampionStreaks,
+    convergence: ConvergenceMonitor,
+    /// Estado de la mutación adaptativa, si la evolución usa `--stagnation-window`.
+    mutation_schedule: Option<MutationSchedule>,
+    rng: ChaCha12Rng,
 }
 
-// --- FUNCIÓN PRINCIPAL (PUNTO DE ENTRADA) ---
-fn main() {
-    let cli = Cli::parse();
-
-    let result = match &cli.command {
-        Commands::Map { universes } => {
-            println!("--- INICIANDO MODO MAPEO ---");
-            run_mapping_mode(*universes)
+impl EvolutionCheckpoint {
+    fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let checkpoint: EvolutionCheckpoint = serde_json::from_str(&fs::read_to_string(path)?)?;
+        if checkpoint.population.len() != POPULATION_SIZE {
+            return Err(format!("{}: la población tiene {} genomas y no {}", path, checkpoint.population.len(), POPULATION_SIZE).into());
         }
-        Commands::Evolve { seed, generations } => {
-            println!("--- INICIANDO MODO EVOLUTIVO ---");
-            run_evolutionary_mode(seed, *generations)
-        }
-    };
+        Ok(checkpoint)
+    }
+}
 
-    if let Err(e) = result {
-        eprintln!("Error en la ejecución: {}", e);
+/// Reabre un CSV cuya primera columna es la generación para seguir escribiendo desde `generation`:
+/// conserva la cabecera y las filas anteriores, y descarta las demás, que se escribieron después
+/// del punto de control y antes de que se interrumpiera la ejecución (incluida una última fila a
+/// medio escribir, que se reconoce porque le faltan columnas o rompe el orden de generaciones).
+fn reopen_generation_csv(path: &str, generation: u64) -> Result<csv::Writer<fs::File>, Box<dyn Error>> {
+    let mut rdr = csv::ReaderBuilder::new().has_headers(false).flexible(true).from_path(path)?;
+    let mut records = rdr.records();
+    let header = records.next().ok_or_else(|| format!("{} está vacío", path))??;
+    let mut previous = 0;
+    let kept: Vec<csv::StringRecord> = records
+        .map_while(Result::ok)
+        .take_while(|record| {
+            let row_generation = record.get(0).and_then(|field| field.parse::<u64>().ok());
+            let keep = record.len() == header.len() && row_generation.is_some_and(|g| g >= previous && g < generation);
+            previous = row_generation.unwrap_or(previous);
+            keep
+        })
+        .collect();
+    let mut wtr = csv::Writer::from_path(path)?;
+    wtr.write_record(&header)?;
+    for record in &kept {
+        wtr.write_record(record)?;
     }
+    Ok(wtr)
 }
 
-// --- LÓGICA DEL MODO MAPEO ---
-fn run_mapping_mode(num_universes: u64) -> Result<(), Box<dyn Error>> {
-    const FITNESS_THRESHOLD_TO_LOG: f64 = 0.0;
-    const SAMPLING_FACTOR: u64 = 100;
+// --- TASAS DE LOS OPERADORES GENÉTICOS ---
+/// Tamaño de la población del AG.
+const POPULATION_SIZE: usize = 100;
 
-    let mut rng = thread_rng();
-    let mut wtr = csv::Writer::from_path("landscape_data.csv")?;
-    wtr.write_record(&[
-        "fitness", "winning_gen", "mass_up_quark", "mass_down_quark", "mass_strange_quark", 
-        "mass_charm_quark", "mass_bottom_quark", "mass_top_quark"
-    ])?;
-
-    println!("Simulando {} universos y muestreando 1 de cada {} candidatos viables...", num_universes, SAMPLING_FACTOR);
-    let mut viable_count: u64 = 0;
-
-    for i in 0..num_universes {
-        let random_laws = CosmicLaw {
-            G: rng.gen_range(6.674e-11..6.674e-10), e: rng.gen_range(0.5e-19..2.5e-19),
-            alpha_s: rng.gen_range(0.1..2.0), alpha_w: rng.gen_range(1.0e-9..1.0e-4),
-            mass_up_quark: rng.gen_range(1.0e-30..6.0e-30), mass_down_quark: rng.gen_range(1.0e-30..1.3e-29),
-            mass_electron: rng.gen_range(1.0e-31..1.0e-30), mass_strange_quark: rng.gen_range(1.0e-29..1.0e-28), 
-            mass_charm_quark: rng.gen_range(1.0e-29..1.0e-27), mass_muon: rng.gen_range(1.0e-29..1.0e-27),
-            mass_bottom_quark: rng.gen_range(1.0e-28..1.0e-27), mass_top_quark: rng.gen_range(1.0e-28..1.0e-25),
-            mass_tauon: rng.gen_range(1.0e-28..1.0e-26),
-        };
-        
-        let (fitness, winning_gen) = calculate_fitness(&random_laws);
+/// Tasas de los operadores del AG, reunidas para validarlas juntas antes de la ejecución. Hay dos
+/// tipos, y cada operador nuevo debe registrarse en el que le corresponda:
+/// - probabilidades por hijo, que se aplican de forma independiente y solo deben estar en [0, 1];
+/// - fracciones que reparten cada generación entre orígenes excluyentes (élite, ...): su suma no
+///   puede superar 1, o la composición de la población quedaría indefinida.
+struct OperatorRates {
+    per_child: Vec<(&'static str, f64)>,
+    population_shares: Vec<(&'static str, f64)>,
+}
 
-        // Añadir al modo mapping
-        if fitness > FITNESS_THRESHOLD_TO_LOG {
-            let universe_type = analyze_universe_type(fitness, winning_gen);
-            if i % 10_000_000 == 0 {
-                println!("Muestra #{}: Fitness {:.4}, Tipo: {}", i, fitness, universe_type);
-            }
+impl OperatorRates {
+    fn from_args(args: &EvolveArgs) -> Self {
+        Self {
+            per_child: vec![("--swap-rate", args.swap_rate), ("--crossover-rate", args.crossover_rate)],
+            // El campeón pasa intacto a la siguiente generación
+            population_shares: vec![("élite", 1.0 / POPULATION_SIZE as f64)],
         }
+    }
 
-        if fitness > FITNESS_THRESHOLD_TO_LOG {
-            viable_count += 1;
-            if viable_count % SAMPLING_FACTOR == 0 {
-                wtr.write_record(&[
-                    format!("{:e}", fitness), winning_gen.to_string(),
-                    format!("{:e}", random_laws.mass_up_quark), format!("{:e}", random_laws.mass_down_quark),
-                    format!("{:e}", random_laws.mass_strange_quark), format!("{:e}", random_laws.mass_charm_quark),
-                    format!("{:e}", random_laws.mass_bottom_quark), format!("{:e}", random_laws.mass_top_quark),
-                ])?;
+    fn validate(&self) -> Result<(), String> {
+        for &(name, rate) in self.per_child.iter().chain(&self.population_shares) {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(format!("La tasa de {} debe ser una probabilidad en [0, 1] (es {})", name, rate));
             }
         }
-        if i > 0 && i % 1_000_000 == 0 {
-            println!("... {} millones de universos mapeados.", i / 1_000_000);
+        let total: f64 = self.population_shares.iter().map(|(_, share)| share).sum();
+        if total > 1.0 + 1e-12 {
+            let shares: Vec<String> = self.population_shares.iter()
+                .map(|(name, share)| format!("{} {:.3}", name, share))
+                .collect();
+            return Err(format!(
+                "Las fracciones de la población suman {:.3} > 1 ({}): no queda sitio para todos los operadores; reduce alguna tasa",
+                total, shares.join(" + ")
+            ));
         }
+        Ok(())
     }
+}
 
-    wtr.flush()?;
-    println!("--- MAPEO COMPLETADO ---");
-    println!("Datos de {} universos guardados en landscape_data.csv", viable_count / SAMPLING_FACTOR);
-    Ok(())
+/// Lee la población sembrada de `--seed-population`: un array JSON no vacío de genomas con todos
+/// los genes finitos y positivos (la población evoluciona en espacio logarítmico).
+fn load_seed_population(path: &str) -> Result<Vec<CosmicLaw>, Box<dyn Error>> {
+    let population: Vec<CosmicLaw> = serde_json::from_str(&fs::read_to_string(path)?)?;
+    if population.is_empty() {
+        return Err(format!("La población sembrada de {} está vacía", path).into());
+    }
+    for (index, laws) in population.iter().enumerate() {
+        laws.validate().map_err(|e| format!("{}: genoma {}: {}", path, index, e))?;
+    }
+    Ok(population)
 }
 
-fn run_evolutionary_mode(seed_file: &str, num_generations: u32) -> Result<(), Box<dyn Error>> {
-    // --- 1. SETUP ---
-    let adam_genome: CosmicLaw = serde_json::from_str(&fs::read_to_string(seed_file)?)?;
-    let mut rng = thread_rng();
-    
-    const POPULATION_SIZE: usize = 100;
+/// Población inicial a partir de `--seed-population`, ajustada a `size` individuos:
+/// - con más genomas que plazas se conservan los `size` más aptos según `evaluate` (a igualdad de
+///   fitness, en el orden del archivo);
+/// - con menos, se conservan todos y las plazas libres se rellenan con mutantes de los genomas
+///   sembrados, tomados por turnos (el primer mutante del primero, el segundo del segundo...).
+fn seeded_population(
+    seeds: &[CosmicLaw],
+    size: usize,
+    evaluate: &(dyn Fn(&CosmicLaw) -> f64 + Sync),
+    mutate: &mut dyn FnMut(&LogGenome) -> LogGenome,
+) -> Vec<LogGenome> {
+    let mut population: Vec<LogGenome> = if seeds.len() > size {
+        let mut ranked: Vec<(f64, &CosmicLaw)> = seeds.iter().map(|laws| (evaluate(laws), laws)).collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+        ranked.iter().take(size).map(|(_, laws)| LogGenome::from_laws(laws)).collect()
+    } else {
+        seeds.iter().map(LogGenome::from_laws).collect()
+    };
+    let seeded = population.len();
+    for k in 0..size - seeded {
+        let parent = population[k % seeded];
+        population.push(mutate(&parent));
+    }
+    population
+}
+
+/// Núcleo del algoritmo genético. Devuelve el campeón de la última generación y su fitness. Con
+/// `quiet` no escribe ningún archivo ni mensaje de progreso (cada reinicio de `--restarts`).
+fn run_genetic_algorithm(
+    args: &EvolveArgs,
+    adam_genome: &CosmicLaw,
+    start: GaStart,
+    target_genome: Option<&CosmicLaw>,
+    evaluate: &(dyn Fn(&CosmicLaw) -> f64 + Sync),
+    rng: &mut ChaCha12Rng,
+    quiet: bool,
+) -> Result<(LogGenome, f64), Box<dyn Error>> {
     const MUTATION_RATE: f64 = 0.10; // 10% de probabilidad por gen
     const TOURNAMENT_SIZE: usize = 3;
     const HYPERMUTATION_CHANCE: f64 = 0.05; // 5% de las mutaciones serán 'saltos de fe'
+    // Ráfaga de diversidad al inicio de cada ronda: mutaciones mucho más frecuentes y agresivas
+    const BURST_MUTATION_RATE: f64 = 0.5;
+    const BURST_HYPERMUTATION_CHANCE: f64 = 0.5;
+    let use_rounds = args.rounds > 1;
 
-    // Preparamos el archivo CSV para registrar los resultados
-    let mut wtr = csv::Writer::from_path("evolution_data.csv")?;
-    wtr.write_record(&["generation", "best_fitness"])?;
+    // Al reanudar, el CSV conserva las filas anteriores al punto de control
+    let first_generation = match &start {
+        GaStart::Resumed(checkpoint) => checkpoint.generation,
+        _ => 0,
+    };
+    let mut wtr = match (quiet, first_generation) {
+        (true, _) => None,
+        (false, 0) => Some(csv::Writer::from_path("evolution_data.csv")?),
+        (false, generation) => Some(reopen_generation_csv("evolution_data.csv", generation)?),
+    };
+    let mut header = vec!["generation", "best_fitness"];
+    if use_rounds {
+        header.push("round");
+    }
+    if args.track_offspring {
+        header.push("best_offspring_fitness");
+    }
+    if args.stagnation_window.is_some() {
+        header.push("mutation_rate");
+    }
+    if args.record_timing {
+        header.push("gen_millis");
+    }
+    if let (Some(wtr), 0) = (wtr.as_mut(), first_generation) {
+        wtr.write_record(&header)?;
+    }
+    // Pesos con los que se describe al campeón en `--output` y junto a cada punto de control
+    let champion_weights = match (quiet, &args.weights) {
+        (true, _) => None,
+        (false, Some(path)) => Some(FitnessWeights::load(path)?),
+        (false, None) => Some(FitnessWeights::default()),
+    };
+    let mut trajectory = match (quiet, args.trajectory.as_deref(), first_generation) {
+        (true, _, _) | (false, None, _) => None,
+        (false, Some(path), 0) => open_trajectory(Some(path))?,
+        (false, Some(path), generation) => Some(reopen_generation_csv(path, generation)?),
+    };
 
     // --- 2. POBLACIÓN INICIAL ---
-    let mut population: Vec<CosmicLaw> = (0..POPULATION_SIZE)
-        .map(|_| adam_genome.mutate(&mut rng, MUTATION_RATE, HYPERMUTATION_CHANCE))
+    // La población vive en espacio log-genético; solo se vuelve a unidades físicas para evaluarla
+    let kernel = args.gaussian_sigma.map_or(MutationKernel::Multiplicative, MutationKernel::Gaussian);
+    let adam_logs = LogGenome::from_laws(adam_genome);
+    // Estado que arrastra el bucle generacional; al reanudar se recupera del punto de control.
+    // `elite_count` es el número de individuos al inicio de la población que son élite heredada (no
+    // descendencia nueva): en la población inicial todos son mutantes nuevos
+    let mut elite_count = 0;
+    let mut best_so_far = f64::NEG_INFINITY;
+    let mut streaks = ChampionStreaks::default();
+    let mut convergence = ConvergenceMonitor::new(args.convergence_window as usize);
+    let mut mutation_schedule = args.stagnation_window.map(|window| MutationSchedule::new(window, args.stagnation_epsilon));
+    let mut population: Vec<LogGenome> = match start {
+        GaStart::Resumed(checkpoint) => {
+            elite_count = checkpoint.elite_count;
+            best_so_far = checkpoint.best_so_far;
+            streaks = checkpoint.streaks;
+            convergence = checkpoint.convergence;
+            mutation_schedule = checkpoint.mutation_schedule.or(mutation_schedule);
+            *rng = checkpoint.rng;
+            checkpoint.population
+        }
+        GaStart::Seeded(seeds) => {
+            if !quiet && seeds.len() != POPULATION_SIZE {
+                progress!(
+                    "La población sembrada tiene {} genomas para {} plazas: {}",
+                    seeds.len(), POPULATION_SIZE,
+                    if seeds.len() > POPULATION_SIZE { "se conservan los más aptos" } else { "se rellena con mutantes" }
+                );
+            }
+            seeded_population(seeds, POPULATION_SIZE, evaluate, &mut |genome| {
+                genome.mutate(rng, MUTATION_RATE, HYPERMUTATION_CHANCE, kernel)
+            })
+        }
+        GaStart::Adam => (0..POPULATION_SIZE)
+            .map(|_| adam_logs.mutate(rng, MUTATION_RATE, HYPERMUTATION_CHANCE, kernel))
+            .collect(),
+    };
+    // Identificador de linaje de cada individuo (0 si no se registra el linaje)
+    let mut lineage = (!quiet && args.newick.is_some()).then(Lineage::new);
+    let mut population_ids: Vec<usize> = population.iter()
+        .map(|genome| lineage.as_mut().map_or(0, |lineage| lineage.add(Lineage::ROOT, 0, genome.distance(&adam_logs))))
         .collect();
 
-    println!("Población inicial creada. Iniciando evolución...");
+    if !quiet && first_generation == 0 {
+        progress!("Población inicial creada. Iniciando evolución...");
+    }
+    let mut final_champion = (adam_logs, f64::NEG_INFINITY);
 
     // --- 3. BUCLE GENERACIONAL ---
-    for generation in 0..num_generations {
+    // Las rondas comparten un contador de generaciones continuo
+    let total_generations = args.generations.checked_mul(args.rounds as u64)
+        .ok_or("El número total de generaciones (generaciones × rondas) no cabe en 64 bits")?;
+    if first_generation >= total_generations {
+        return Err(format!("El punto de control está en la generación {} y la evolución solo tiene {}", first_generation, total_generations).into());
+    }
+    if first_generation > 0 {
+        progress!("Reanudando en la generación {} de {}...", first_generation, total_generations);
+    }
+    let bar = if quiet { ProgressBar::hidden() } else { progress_bar(total_generations, EVOLVE_BAR_TEMPLATE) };
+    bar.set_position(first_generation);
+    for generation in first_generation..total_generations {
+        let round = generation / args.generations;
+        if generation > 0 && generation % args.generations == 0 {
+            // Nueva ronda: el campeón anterior se conserva y el resto se re-siembra a partir de él con hipermutación
+            let round_seed = population[0];
+            population = std::iter::once(round_seed)
+                .chain((1..POPULATION_SIZE).map(|_| round_seed.mutate(rng, BURST_MUTATION_RATE, BURST_HYPERMUTATION_CHANCE, kernel)))
+                .collect();
+            if !quiet {
+                bar.suspend(|| progress!("--- RONDA {} --- (semilla con fitness {:.6})", round, evaluate(&round_seed.to_laws())));
+            }
+        }
+
+        let generation_start = Instant::now();
+
         // a. Evaluar a toda la población
-        let mut evaluated_population: Vec<(CosmicLaw, f64)> = population.iter()
-            .map(|laws| (laws.clone(), calculate_fitness(laws).0))
+        let evaluated_population: Vec<(LogGenome, f64)> = population.iter()
+            .map(|genome| (*genome, evaluate(&genome.to_laws())))
             .collect();
+
+        // Mejor fitness entre la descendencia nueva, calculado antes de ordenar (la élite ocupa las primeras posiciones)
+        let best_offspring_fitness = evaluated_population[elite_count..].iter()
+            .map(|(_, fitness)| *fitness)
+            .fold(f64::NEG_INFINITY, f64::max);
         
-        // Ordenamos para encontrar al campeón de esta generación
-        evaluated_population.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        // Ordenamos para encontrar al campeón de esta generación (los identificadores de linaje
+        // siguen el mismo orden)
+        let mut order: Vec<usize> = (0..evaluated_population.len()).collect();
+        order.sort_by(|&a, &b| evaluated_population[b].1.partial_cmp(&evaluated_population[a].1).unwrap());
+        let evaluated_ids: Vec<usize> = order.iter().map(|&k| population_ids[k]).collect();
+        let evaluated_population: Vec<(LogGenome, f64)> = order.iter().map(|&k| evaluated_population[k]).collect();
         
         let champion = &evaluated_population[0];
+        final_champion = *champion;
+        let champion_laws = champion.0.to_laws();
+        if args.emit_seed_on_improvement && champion.1 > best_so_far {
+            write_json_atomically(BEST_SO_FAR_FILE, &champion_laws)?;
+        }
+        best_so_far = best_so_far.max(champion.1);
+        streaks.observe(generation, &champion_laws);
+        if let Some(state) = convergence.observe(champion.1) {
+            if !quiet {
+                bar.suspend(|| progress!("Generación {}: la curva de fitness está {}", generation, state.description()));
+            }
+        }
+        let (mutation_rate, hypermutation_chance) = match mutation_schedule.as_mut() {
+            Some(schedule) => {
+                if schedule.observe(champion.1) && !quiet {
+                    bar.suspend(|| progress!("Generación {}: {} generaciones sin mejora, se refuerza la mutación", generation, schedule.window));
+                }
+                schedule.rates(MUTATION_RATE, HYPERMUTATION_CHANCE)
+            }
+            None => (MUTATION_RATE, HYPERMUTATION_CHANCE),
+        };
         
         // Escribir los datos del campeón en el archivo CSV
-        wtr.write_record(&[
+        let mut record = vec![
             generation.to_string(),
-            champion.1.to_string(),
-        ])?;
+            csv_float(champion.1),
+        ];
+        if use_rounds {
+            record.push(round.to_string());
+        }
+        if args.track_offspring {
+            record.push(csv_float(best_offspring_fitness));
+        }
+        if mutation_schedule.is_some() {
+            record.push(csv_float(mutation_rate));
+        }
+        if let Some(trajectory) = trajectory.as_mut() {
+            if generation % args.trajectory_every == 0 {
+                write_trajectory_rows(trajectory, generation, &champion_laws)?;
+            }
+        }
         
         // b, c. Crear la nueva generación
         let mut next_population = Vec::with_capacity(POPULATION_SIZE);
+        let mut next_ids = Vec::with_capacity(POPULATION_SIZE);
         // Elitismo: El campeón pasa directamente a la siguiente generación sin mutar
-        next_population.push(champion.0.clone());
+        next_population.push(champion.0);
+        next_ids.push(evaluated_ids[0]);
 
         // Llenar el resto de la población mediante selección y mutación
         for _ in 1..POPULATION_SIZE {
             // Seleccionar un padre mediante torneo
-            let mut tournament_contenders = Vec::with_capacity(TOURNAMENT_SIZE);
-            for _ in 0..TOURNAMENT_SIZE {
-                let random_index = rng.gen_range(0..evaluated_population.len());
-                tournament_contenders.push(&evaluated_population[random_index]);
-            }
-            let parent = tournament_contenders.iter().max_by(|a, b| a.1.partial_cmp(&b.1).unwrap()).unwrap();
+            let parent = tournament_select(&evaluated_population, TOURNAMENT_SIZE, args.tournament_replacement, rng);
+            let parent_genome = evaluated_population[parent].0;
 
-            // Crear un hijo mutando al padre y añadirlo a la nueva población
-            let child = parent.0.mutate(&mut rng, MUTATION_RATE, HYPERMUTATION_CHANCE);
+            // Crear un hijo mutando al padre (o a su recombinación con un segundo padre) y añadirlo a
+            // la nueva población. Sin recombinación no se consume ningún número aleatorio extra, de
+            // modo que una semilla da la misma evolución que antes de existir el operador
+            let mut child = if args.crossover_rate > 0.0 && rng.gen::<f64>() < args.crossover_rate {
+                let mate = tournament_select(&evaluated_population, TOURNAMENT_SIZE, args.tournament_replacement, rng);
+                parent_genome.crossover(&evaluated_population[mate].0, args.crossover_blend, rng)
+            } else {
+                parent_genome
+            };
+            child = child.mutate(rng, mutation_rate, hypermutation_chance, kernel);
+            if rng.gen::<f64>() < args.swap_rate {
+                child = child.swap_genes(rng);
+            }
+            next_ids.push(lineage.as_mut().map_or(0, |lineage| {
+                lineage.add(evaluated_ids[parent], generation + 1, child.distance(&parent_genome))
+            }));
             next_population.push(child);
         }
         
         population = next_population;
+        population_ids = next_ids;
+        elite_count = 1;
+
+        // La fila se escribe tras la reproducción para que `gen_millis` cubra la generación completa
+        if args.record_timing {
+            record.push(csv_float(generation_start.elapsed().as_secs_f64() * 1e3));
+        }
+        let Some(wtr) = wtr.as_mut() else { continue };
+        wtr.write_record(&record)?;
+        bar.set_message(format!("mejor fitness {:.6}", champion.1));
+        bar.inc(1);
 
         // Informar del progreso en la consola cada 10 generaciones
         if generation % 10 == 0 {
-             println!("Generación: {}, Mejor Fitness: {:.6}", generation, champion.1);
+             bar.suspend(|| progress!("Generación: {}, Mejor Fitness: {:.6}", generation, champion.1));
+        }
+        if JSON_LOGS.load(Ordering::Relaxed) {
+            let log_population: Vec<LogGenome> = evaluated_population.iter().map(|(genome, _)| *genome).collect();
+            ProgressEvent::new("generation", generation, champion.1)
+                .with_diversity(population_diversity(&log_population))
+                .with_mutation_rate(mutation_rate)
+                .emit();
+        }
+
+        if let Some(interval) = args.checkpoint_interval {
+            let next_generation = generation + 1;
+            if next_generation % interval == 0 && next_generation < total_generations {
+                // Los CSV se vuelcan antes: así contienen todas las filas anteriores al punto de control
+                wtr.flush()?;
+                if let Some(trajectory) = trajectory.as_mut() {
+                    trajectory.flush()?;
+                }
+                let checkpoint = EvolutionCheckpoint {
+                    generation: next_generation,
+                    adam: adam_genome.clone(),
+                    population: population.clone(),
+                    elite_count,
+                    best_so_far,
+                    streaks: streaks.clone(),
+                    convergence: convergence.clone(),
+                    mutation_schedule: mutation_schedule.clone(),
+                    rng: rng.clone(),
+                };
+                let path = format!("checkpoint_gen_{}.json", next_generation);
+                write_json_atomically(&path, &checkpoint)?;
+                if let Some(weights) = &champion_weights {
+                    write_json_atomically(&format!("checkpoint_gen_{}.champion.json", next_generation), &ChampionRecord::new(champion_laws.clone(), weights))?;
+                }
+                bar.suspend(|| progress!("Punto de control guardado en {}", path));
+            }
         }
     }
+    bar.finish_and_clear();
     
     // Asegurarse de que todos los datos se escriben en el disco
+    let Some(mut wtr) = wtr else { return Ok(final_champion) };
     wtr.flush()?;
-    println!("--- EVOLUCIÓN COMPLETADA ---");
-    println!("Resultados guardados en evolution_data.csv");
-    Ok(())
+    if let Some(trajectory) = trajectory.as_mut() {
+        trajectory.flush()?;
+    }
+    progress!("--- EVOLUCIÓN COMPLETADA ---");
+    streaks.report();
+    convergence.report();
+    if let Some(target) = target_genome {
+        let target_logs = LogGenome::from_laws(target);
+        let closest = population.iter()
+            .map(|genome| genome.distance(&target_logs))
+            .fold(f64::INFINITY, f64::min);
+        progress!("Distancia log-genética mínima al objetivo: {:.6e}", closest);
+    }
+    if args.objective == Objective::MinimizeResidual {
+        print_residual_report(&population[0].to_laws());
+    }
+    if let (Some(lineage), Some(path)) = (&lineage, &args.newick) {
+        fs::write(path, lineage.to_newick(&population_ids))?;
+        progress!("Árbol filogenético de la población final guardado en {}", path);
+    }
+    if let Some(weights) = &champion_weights {
+        write_json_atomically(&args.output, &ChampionRecord::new(final_champion.0.to_laws(), weights))?;
+        progress!("Genoma campeón guardado en {}", args.output);
+    }
+    progress!("Resultados guardados en evolution_data.csv");
+    Ok(final_champion)
 }
 
-// --- IMPLEMENTACIÓN DE LA LÓGICA DE MUTACIÓN (CON HIPERMUTACIÓN) ---
-impl CosmicLaw {
-    /// Aplica una mutación a una copia del genoma, con posibilidad de hipermutación.
-    fn mutate(&self, rng: &mut impl Rng, rate: f64, hypermutation_chance: f64) -> Self {
-        let mut new_laws = self.clone();
+// --- LINAJE Y ÁRBOL FILOGENÉTICO ---
+/// Grafo de ascendencia de todos los individuos creados por el AG: cada nodo apunta a su padre.
+/// El campeón que pasa intacto a la siguiente generación conserva su nodo.
+struct Lineage {
+    nodes: Vec<LineageNode>,
+}
 
-        if rng.gen::<f64>() < rate {
-            if rng.gen::<f64>() < hypermutation_chance {
-                new_laws.G = rng.gen_range(6.674e-11..6.674e-10);
-            } else {
-                new_laws.G *= rng.gen_range(0.95..1.05);
+struct LineageNode {
+    parent: Option<usize>,
+    generation: u64,
+    /// Distancia log-genética al padre.
+    branch_length: f64,
+}
+
+impl Lineage {
+    /// Nodo raíz: el genoma semilla.
+    const ROOT: usize = 0;
+
+    fn new() -> Self {
+        Self { nodes: vec![LineageNode { parent: None, generation: 0, branch_length: 0.0 }] }
+    }
+
+    fn add(&mut self, parent: usize, generation: u64, branch_length: f64) -> usize {
+        self.nodes.push(LineageNode { parent: Some(parent), generation, branch_length });
+        self.nodes.len() - 1
+    }
+
+    fn label(&self, node: usize) -> String {
+        if node == Self::ROOT { "semilla".to_string() } else { format!("n{}_g{}", node, self.nodes[node].generation) }
+    }
+
+    /// Árbol en formato Newick que une a los `survivors` con la semilla. Los tramos sin
+    /// ramificaciones se colapsan en una sola rama cuya longitud es la suma de las distancias.
+    fn to_newick(&self, survivors: &[usize]) -> String {
+        let mut is_survivor = vec![false; self.nodes.len()];
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); self.nodes.len()];
+        let mut included = vec![false; self.nodes.len()];
+        included[Self::ROOT] = true;
+        for &survivor in survivors {
+            is_survivor[survivor] = true;
+            // Subir por la ascendencia hasta un antepasado ya incluido
+            let mut node = survivor;
+            while !included[node] {
+                included[node] = true;
+                let parent = self.nodes[node].parent.expect("solo la raíz no tiene padre");
+                children[parent].push(node);
+                node = parent;
             }
         }
-        if rng.gen::<f64>() < rate {
-            if rng.gen::<f64>() < hypermutation_chance {
-                new_laws.e = rng.gen_range(0.5e-19..2.5e-19);
-            } else {
-                new_laws.e *= rng.gen_range(0.95..1.05);
+        let mut newick = String::new();
+        self.write_newick(Self::ROOT, &children, &is_survivor, &mut newick);
+        newick.push_str(";\n");
+        newick
+    }
+
+    fn write_newick(&self, node: usize, children: &[Vec<usize>], is_survivor: &[bool], out: &mut String) {
+        if !children[node].is_empty() {
+            out.push('(');
+            for (k, &child) in children[node].iter().enumerate() {
+                if k > 0 {
+                    out.push(',');
+                }
+                let (mut end, mut length) = (child, self.nodes[child].branch_length);
+                while !is_survivor[end] && children[end].len() == 1 {
+                    end = children[end][0];
+                    length += self.nodes[end].branch_length;
+                }
+                self.write_newick(end, children, is_survivor, out);
+                let _ = write!(out, ":{}", length);
             }
+            out.push(')');
         }
-        if rng.gen::<f64>() < rate {
-            if rng.gen::<f64>() < hypermutation_chance {
-                new_laws.alpha_s = rng.gen_range(0.1..2.0);
-            } else {
-                new_laws.alpha_s *= rng.gen_range(0.95..1.05);
-            }
+        out.push_str(&newick_label(&self.label(node)));
+    }
+}
+
+/// Etiqueta Newick: se entrecomilla (duplicando las comillas simples) si contiene caracteres
+/// reservados por el formato.
+fn newick_label(label: &str) -> String {
+    if label.chars().any(|c| "()[]':;,".contains(c) || c.is_whitespace()) {
+        format!("'{}'", label.replace('\'', "''"))
+    } else {
+        label.to_string()
+    }
+}
+
+// --- REINICIOS MÚLTIPLES ---
+/// Archivos de salida de `--restarts`.
+const RESTARTS_FILE: &str = "restarts.csv";
+const BEST_RESTART_FILE: &str = "best_restart.json";
+
+/// Lanza `restarts` evoluciones independientes del AG en paralelo y resume sus resultados: la
+/// dispersión de los fitness finales indica cuán rugoso es el paisaje alrededor de la semilla.
+fn run_restarts(
+    args: &EvolveArgs,
+    pool: RngPool,
+    adam_genome: &CosmicLaw,
+    seed_population: Option<&[CosmicLaw]>,
+    target_genome: Option<&CosmicLaw>,
+    evaluate: &(dyn Fn(&CosmicLaw) -> f64 + Sync),
+    weights: &FitnessWeights,
+) -> Result<(), Box<dyn Error>> {
+    let restarts = args.restarts.unwrap_or(1);
+    progress!("Lanzando {} evoluciones independientes de {} generaciones...", restarts, args.generations);
+    let champions = (0..restarts as u64)
+        .into_par_iter()
+        .map(|restart| {
+            let mut rng = pool.stream(restart);
+            let start = seed_population.map_or(GaStart::Adam, GaStart::Seeded);
+            run_genetic_algorithm(args, adam_genome, start, target_genome, evaluate, &mut rng, true).map_err(|e| e.to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (best_restart, &(best_genome, best_fitness)) = champions.iter().enumerate()
+        .max_by(|a, b| a.1.1.total_cmp(&b.1.1))
+        .ok_or("No se ejecutó ninguna evolución")?;
+
+    let mut wtr = csv::Writer::from_path(RESTARTS_FILE)?;
+    write_run_metadata([RESTARTS_FILE], "evolve", args, Some(pool.base_seed), Some(weights))?;
+    wtr.write_record(["restart", "best_fitness", "level", "distance_to_best"])?;
+    for (restart, (genome, fitness)) in champions.iter().enumerate() {
+        let level = calculate_fitness(&genome.to_laws(), weights).level;
+        wtr.write_record([restart.to_string(), csv_float(*fitness), level.to_string(), csv_float(genome.distance(&best_genome))])?;
+    }
+    wtr.flush()?;
+    write_json_atomically(BEST_RESTART_FILE, &best_genome.to_laws())?;
+
+    let mut fitnesses: Vec<f64> = champions.iter().map(|(_, fitness)| *fitness).collect();
+    fitnesses.sort_by(f64::total_cmp);
+    let n = fitnesses.len() as f64;
+    let mean = fitnesses.iter().sum::<f64>() / n;
+    let std_dev = (fitnesses.iter().map(|f| (f - mean).powi(2)).sum::<f64>() / n).sqrt();
+    let reached_best = fitnesses.iter().filter(|&&f| best_fitness - f <= 1e-9 * best_fitness.abs().max(1.0)).count();
+    progress!("--- REINICIOS COMPLETADOS ---");
+    progress!(
+        "Fitness final: mínimo {:.6}, mediana {:.6}, media {:.6} ± {:.6}, máximo {:.6}",
+        fitnesses[0], fitnesses[fitnesses.len() / 2], mean, std_dev, best_fitness
+    );
+    progress!("{} de {} evoluciones alcanzaron el mejor fitness (reinicio {})", reached_best, restarts, best_restart);
+    progress!("Resumen guardado en {}; mejor genoma en {}", RESTARTS_FILE, BEST_RESTART_FILE);
+    Ok(())
+}
+
+/// Devuelve el índice del mejor de `size` contendientes elegidos al azar. Sin reemplazo, el tamaño efectivo se
+/// acota al de la población: un torneo del tamaño de la población siempre elige al campeón.
+fn tournament_select(
+    population: &[(LogGenome, f64)],
+    size: usize,
+    replacement: TournamentReplacement,
+    rng: &mut impl Rng,
+) -> usize {
+    let contenders: Vec<usize> = match replacement {
+        TournamentReplacement::With => (0..size.max(1)).map(|_| rng.gen_range(0..population.len())).collect(),
+        TournamentReplacement::Without => {
+            let effective_size = size.clamp(1, population.len());
+            rand::seq::index::sample(rng, population.len(), effective_size).into_vec()
         }
-        if rng.gen::<f64>() < rate {
-            if rng.gen::<f64>() < hypermutation_chance {
-                new_laws.alpha_w = rng.gen_range(1.0e-9..1.0e-4);
-            } else {
-                new_laws.alpha_w *= rng.gen_range(0.95..1.05);
-            }
+    };
+    contenders.into_iter()
+        .max_by(|&a, &b| population[a].1.partial_cmp(&population[b].1).unwrap())
+        .unwrap()
+}
+
+/// Desglose de los residuos del campeón y su distancia log-genética a nuestro universo.
+fn print_residual_report(laws: &CosmicLaw) {
+    let engine = PhysicsEngine::new(laws.clone(), PhysicalConstants::default());
+    progress!("Residuos relativos frente a nuestro universo:");
+    for (name, residual) in engine.observable_residuals(&PhysicsEngine::from_real_universe()) {
+        progress!("  {:<20} {:.6e}", name, residual);
+    }
+    progress!("Distancia log-genética a nuestro universo: {:.6e}", laws.log_distance(&REAL_UNIVERSE));
+}
+
+// --- OPTIMIZACIÓN POR ENJAMBRE DE PARTÍCULAS (PSO) ---
+/// Una partícula del enjambre: posición y velocidad viven en espacio log-genético.
+struct Particle {
+    position: LogGenome,
+    velocity: [f64; GENE_COUNT],
+    fitness: f64,
+    personal_best: (LogGenome, f64),
+}
+
+fn run_particle_swarm(
+    args: &EvolveArgs,
+    adam_genome: &CosmicLaw,
+    evaluate: &dyn Fn(&CosmicLaw) -> f64,
+    rng: &mut impl Rng,
+) -> Result<(), Box<dyn Error>> {
+    const SWARM_SIZE: usize = 100;
+    const MUTATION_RATE: f64 = 0.10;
+    const HYPERMUTATION_CHANCE: f64 = 0.05;
+    // Velocidad máxima por dimensión (unidades de logaritmo natural) para evitar que el enjambre explote
+    const MAX_VELOCITY: f64 = 1.0;
+    const INITIAL_VELOCITY: f64 = 0.05;
+
+    let mut wtr = csv::Writer::from_path("evolution_data.csv")?;
+    let mut header = vec!["generation", "best_fitness"];
+    if args.track_offspring {
+        header.push("best_offspring_fitness");
+    }
+    if args.record_timing {
+        header.push("gen_millis");
+    }
+    wtr.write_record(&header)?;
+    let mut trajectory = open_trajectory(args.trajectory.as_deref())?;
+
+    // Las posiciones iniciales se dispersan alrededor de la semilla igual que la población del AG
+    let mut swarm: Vec<Particle> = (0..SWARM_SIZE)
+        .map(|_| {
+            let position = LogGenome::from_laws(adam_genome)
+                .mutate(rng, MUTATION_RATE, HYPERMUTATION_CHANCE, MutationKernel::Multiplicative);
+            let velocity = std::array::from_fn(|_| rng.gen_range(-INITIAL_VELOCITY..INITIAL_VELOCITY));
+            let fitness = evaluate(&position.to_laws());
+            Particle { position, velocity, fitness, personal_best: (position, fitness) }
+        })
+        .collect();
+    let mut global_best = swarm.iter()
+        .map(|p| p.personal_best)
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+        .unwrap();
+
+    progress!("Enjambre inicial creado. Iniciando PSO...");
+    let mut streaks = ChampionStreaks::default();
+    // El mejor global nunca empeora: el diagnóstico sigue al mejor del enjambre en cada iteración
+    let mut convergence = ConvergenceMonitor::new(args.convergence_window as usize);
+
+    let bar = progress_bar(args.generations, EVOLVE_BAR_TEMPLATE);
+    for generation in 0..args.generations {
+        let generation_start = Instant::now();
+        let best_current = swarm.iter().map(|p| p.fitness).fold(f64::NEG_INFINITY, f64::max);
+        if let Some(state) = convergence.observe(best_current) {
+            bar.suspend(|| progress!("Generación {}: la curva de fitness está {}", generation, state.description()));
         }
-        if rng.gen::<f64>() < rate {
-            if rng.gen::<f64>() < hypermutation_chance {
-                new_laws.mass_up_quark = rng.gen_range(1.0e-30..6.0e-30);
-            } else {
-                new_laws.mass_up_quark *= rng.gen_range(0.95..1.05);
-            }
+
+        let mut record = vec![generation.to_string(), csv_float(global_best.1)];
+        if args.track_offspring {
+            record.push(csv_float(best_current));
         }
-        if rng.gen::<f64>() < rate {
-            if rng.gen::<f64>() < hypermutation_chance {
-                new_laws.mass_down_quark = rng.gen_range(1.0e-30..1.3e-29);
-            } else {
-                new_laws.mass_down_quark *= rng.gen_range(0.95..1.05);
+        if let Some(trajectory) = trajectory.as_mut() {
+            if generation % args.trajectory_every == 0 {
+                write_trajectory_rows(trajectory, generation, &global_best.0.to_laws())?;
             }
         }
-        if rng.gen::<f64>() < rate {
-            if rng.gen::<f64>() < hypermutation_chance {
-                new_laws.mass_electron = rng.gen_range(1.0e-31..1.0e-30);
-            } else {
-                new_laws.mass_electron *= rng.gen_range(0.95..1.05);
+
+        // Actualización de velocidades y posiciones hacia el mejor personal y el mejor global
+        for particle in swarm.iter_mut() {
+            for d in 0..GENE_COUNT {
+                let r1: f64 = rng.gen();
+                let r2: f64 = rng.gen();
+                let v = args.inertia * particle.velocity[d]
+                    + args.cognitive * r1 * (particle.personal_best.0.0[d] - particle.position.0[d])
+                    + args.social * r2 * (global_best.0.0[d] - particle.position.0[d]);
+                particle.velocity[d] = v.clamp(-MAX_VELOCITY, MAX_VELOCITY);
+                particle.position.0[d] += particle.velocity[d];
             }
-        }
-        if rng.gen::<f64>() < rate {
-            if rng.gen::<f64>() < hypermutation_chance {
-                new_laws.mass_strange_quark = rng.gen_range(1.0e-29..1.0e-28);
-            } else {
-                new_laws.mass_strange_quark *= rng.gen_range(0.95..1.05);
+            particle.fitness = evaluate(&particle.position.to_laws());
+            if particle.fitness > particle.personal_best.1 {
+                particle.personal_best = (particle.position, particle.fitness);
             }
         }
-        if rng.gen::<f64>() < rate {
-            if rng.gen::<f64>() < hypermutation_chance {
-                new_laws.mass_charm_quark = rng.gen_range(1.0e-29..1.0e-27);
-            } else {
-                new_laws.mass_charm_quark *= rng.gen_range(0.95..1.05);
+        streaks.observe(generation, &global_best.0.to_laws());
+        let previous_best = global_best.1;
+        for particle in &swarm {
+            if particle.personal_best.1 > global_best.1 {
+                global_best = particle.personal_best;
             }
         }
-        if rng.gen::<f64>() < rate {
-            if rng.gen::<f64>() < hypermutation_chance {
-                new_laws.mass_muon = rng.gen_range(1.0e-29..1.0e-27);
-            } else {
-                new_laws.mass_muon *= rng.gen_range(0.95..1.05);
-            }
+        if args.emit_seed_on_improvement && (generation == 0 || global_best.1 > previous_best) {
+            write_json_atomically(BEST_SO_FAR_FILE, &global_best.0.to_laws())?;
         }
-        if rng.gen::<f64>() < rate {
-            if rng.gen::<f64>() < hypermutation_chance {
-                new_laws.mass_bottom_quark = rng.gen_range(1.0e-28..1.0e-27);
-            } else {
-                new_laws.mass_bottom_quark *= rng.gen_range(0.95..1.05);
-            }
+        if args.record_timing {
+            record.push(csv_float(generation_start.elapsed().as_secs_f64() * 1e3));
         }
-        if rng.gen::<f64>() < rate {
-            if rng.gen::<f64>() < hypermutation_chance {
-                new_laws.mass_top_quark = rng.gen_range(1.0e-28..1.0e-25);
-            } else {
-                new_laws.mass_top_quark *= rng.gen_range(0.95..1.05);
-            }
+        wtr.write_record(&record)?;
+        bar.set_message(format!("mejor fitness {:.6}", global_best.1));
+        bar.inc(1);
+
+        if generation % 10 == 0 {
+            bar.suspend(|| progress!("Generación: {}, Mejor Fitness: {:.6}", generation, global_best.1));
         }
-        if rng.gen::<f64>() < rate {
-            if rng.gen::<f64>() < hypermutation_chance {
-                new_laws.mass_tauon = rng.gen_range(1.0e-28..1.0e-26);
-            } else {
-                new_laws.mass_tauon *= rng.gen_range(0.95..1.05);
-            }
+        if JSON_LOGS.load(Ordering::Relaxed) {
+            let positions: Vec<LogGenome> = swarm.iter().map(|p| p.position).collect();
+            ProgressEvent::new("generation", generation, global_best.1)
+                .with_diversity(population_diversity(&positions))
+                .emit();
+        }
+    }
+    bar.finish_and_clear();
+
+    wtr.flush()?;
+    if let Some(trajectory) = trajectory.as_mut() {
+        trajectory.flush()?;
+    }
+    progress!("--- PSO COMPLETADO ---");
+    streaks.report();
+    convergence.report();
+    progress!("Mejor fitness global: {:.6}", global_best.1);
+    if args.objective == Objective::MinimizeResidual {
+        print_residual_report(&global_best.0.to_laws());
+    }
+    let weights = match &args.weights {
+        Some(path) => FitnessWeights::load(path)?,
+        None => FitnessWeights::default(),
+    };
+    write_json_atomically(&args.output, &ChampionRecord::new(global_best.0.to_laws(), &weights))?;
+    progress!("Genoma campeón guardado en {}", args.output);
+    progress!("Resultados guardados en evolution_data.csv");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Comprueba todas las relaciones de escala de `observable` sobre nuestro universo.
+    fn assert_scaling(observable: &str) {
+        let base = PhysicsEngine::from_real_universe();
+        let relations: Vec<ScalingRelation> = scaling_relations().into_iter().filter(|r| r.observable == observable).collect();
+        assert!(!relations.is_empty(), "no hay relaciones de escala para {}", observable);
+        for relation in relations {
+            let measured = relation.measured_exponent(&base);
+            assert!(
+                (measured - relation.exponent).abs() < SCALING_TOLERANCE,
+                "{} ∝ {}^{} (medido {})", observable, relation.scaled.name(), relation.exponent, measured,
+            );
+        }
+        for invariant in mass_invariants().into_iter().filter(|i| i.observable == observable) {
+            let deviation = invariant.max_deviation(&base);
+            assert!(deviation < SCALING_TOLERANCE, "{} cambia con las masas (desviación {})", invariant.invariant, deviation);
+        }
+    }
+
+    #[test]
+    fn nucleon_mass_genes_move_the_proton_mass() {
+        let base = PhysicsEngine::from_real_universe();
+        for gene in NUCLEON_MASS_GENES {
+            let scaled = base.with_override(gene, base.laws.gene(gene) * MASS_SCALING_FACTOR);
+            assert!((scaled.laws.proton_mass() / base.laws.proton_mass() - 1.0).abs() > 0.1, "{}", gene.spec().name);
+        }
+    }
+
+    #[test]
+    fn chandrasekhar_mass_scales_analytically() {
+        assert_scaling("chandrasekhar_mass");
+    }
+
+    #[test]
+    fn bohr_radius_scales_analytically() {
+        assert_scaling("bohr_radius");
+    }
+
+    #[test]
+    fn alpha_scales_analytically() {
+        assert_scaling("alpha");
+    }
+
+    #[test]
+    fn deuteron_binding_scales_analytically() {
+        assert_scaling("deuteron_binding");
+    }
+
+    #[test]
+    fn pp_gamow_exponent_scales_analytically() {
+        assert_scaling("pp_gamow_exponent");
+    }
+
+    #[test]
+    fn dimension_check_passes() {
+        assert!(run_dimension_check().is_ok());
+    }
+
+    #[test]
+    fn parse_count_rejects_zero_and_accepts_one() {
+        assert!(parse_count("0").is_err());
+        assert_eq!(parse_count("1"), Ok(1));
+    }
+
+    #[test]
+    fn zero_counts_are_rejected_at_argument_parsing() {
+        let parses = |args: &[&str]| Cli::try_parse_from(std::iter::once("rust-universe").chain(args.iter().copied())).is_ok();
+        assert!(!parses(&["map", "--universes", "0"]));
+        assert!(parses(&["map", "--universes", "1"]));
+        assert!(!parses(&["evolve", "-s", "adam.json", "--generations", "0"]));
+        assert!(parses(&["evolve", "-s", "adam.json", "--generations", "1"]));
+        assert!(!parses(&["evolve", "-s", "adam.json", "--rounds", "0"]));
+        assert!(parses(&["evolve", "-s", "adam.json", "--rounds", "1"]));
+    }
+
+    #[test]
+    fn empty_mapping_outcomes_have_zero_fractions() {
+        let empty = MappingOutcomes::default();
+        assert_eq!(empty.total(), 0);
+        assert_eq!(empty.fractions(), [0.0; 6]);
+        assert_eq!(empty.resample(&mut StdRng::seed_from_u64(420)).total(), 0);
+    }
+
+    #[test]
+    fn corrupted_landscape_rows_are_counted_not_fatal() {
+        let header: Vec<&str> = GENES.iter().map(|spec| spec.name).chain(["fitness", "winning_gen"]).collect();
+        let genome: Vec<String> = REAL_UNIVERSE.genes().iter().map(|gene| format!("{:e}", gene)).collect();
+        let row = |genes: &[String], tail: &str| format!("{},{}", genes.join(","), tail);
+        let mut comma_decimal = genome.clone();
+        comma_decimal[1] = "\"1,6e-19\"".to_string();
+        let csv = [
+            header.join(","),
+            row(&genome, "1.5,4"),
+            // Fila truncada: le faltan las últimas columnas
+            genome[..5].join(","),
+            row(&comma_decimal, "1.5,4"),
+            row(&genome, "NaN-ish,4"),
+            row(&genome, "0.5,2"),
+        ].join("\n");
+        let path = std::env::temp_dir().join(format!("rust_universe_corrupted_{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        fs::write(path, csv).unwrap();
+        let result = read_landscape_rows(path);
+        fs::remove_file(path).unwrap();
+
+        let (rows, skipped) = result.unwrap();
+        assert_eq!(rows.iter().map(|row| row.level).collect::<Vec<_>>(), [4, 2]);
+        assert_eq!(skipped.count, 3);
+        assert!(skipped.first_error.unwrap().starts_with("fila 2:"));
+    }
+
+    #[test]
+    fn parse_genome_rejects_malformed_cells() {
+        let headers = csv::StringRecord::from(GENES.iter().map(|spec| spec.name).collect::<Vec<_>>());
+        let gene_cols = genome_columns(&headers, "test.csv").unwrap();
+        let mut cells: Vec<String> = REAL_UNIVERSE.genes().iter().map(|gene| format!("{:e}", gene)).collect();
+        assert!(parse_genome(&csv::StringRecord::from(cells.clone()), &gene_cols).is_ok());
+        let truncated = csv::StringRecord::from(cells[..3].to_vec());
+        assert!(parse_genome(&truncated, &gene_cols).unwrap_err().contains(GENES[3].name));
+        for bad in ["", "1,5", "NaN", "-1e-30", "inf"] {
+            cells[2] = bad.to_string();
+            let error = parse_genome(&csv::StringRecord::from(cells.clone()), &gene_cols).unwrap_err();
+            assert!(error.contains(GENES[2].name), "{}", error);
         }
+    }
 
-        new_laws
+    #[test]
+    fn bootstrap_rejects_empty_outcomes() {
+        let path = std::env::temp_dir().join(format!("rust_universe_empty_outcomes_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        fs::write(path, serde_json::to_string(&MappingOutcomes::default()).unwrap()).unwrap();
+        let result = run_bootstrap_mode(path, 10, 0.95);
+        fs::remove_file(path).unwrap();
+        assert!(result.is_err());
     }
-}
\ No newline at end of file
+}